@@ -0,0 +1,471 @@
+/// Load generator - a real server, a crowd of headless clients and the numbers that come out
+///
+/// `run` starts `lanterm host` as a child process (or uses `--addr`), connects the clients through
+/// `HeadlessClient`, and has the seated ones send inputs at a steady rate. Everyone counts what they
+/// receive; senders time each input until the server has sent out the views it caused. The server's memory comes from
+/// `/proc`, and its "fell behind" warnings count the messages slow readers lost.
+use std::fmt;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant, MissedTickBehavior};
+use futures_util::StreamExt;
+
+use crate::client::websocket_client::WebSocketGameClient;
+use crate::core::context::{self, Player, PlayerRole};
+use crate::core::game::WebSocketGame;
+use crate::core::protocol::{self, ClientMessage, ErrorCode, Hello, ServerInfo, ServerMessage};
+use crate::core::websocket::LAGGED;
+
+/// An input that isn't answered after this long counts as lost
+const INPUT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the spawned server gets to start listening
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What to throw at the server - built from the `lanterm bench` flags
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// Connections to open - the first `MAX_PLAYERS` take seats, the rest watch
+    pub clients: usize,
+    /// How long the seated clients keep sending
+    pub duration: Duration,
+    /// Inputs per second from each seated client
+    pub rate: f64,
+    /// This many clients (the last ones) pause after every frame they read
+    pub slow: usize,
+    pub slow_delay: Duration,
+    /// Lines to send, in turn, through `parse_line` - empty lets the game's bot pick
+    pub inputs: Vec<String>,
+    /// Bench a server that's already running instead of starting one
+    pub addr: Option<String>,
+    /// Seed for the spawned server and the bots
+    pub seed: u64,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            clients: 50,
+            duration: Duration::from_secs(10),
+            rate: 10.0,
+            slow: 0,
+            slow_delay: Duration::from_millis(20),
+            inputs: Vec::new(),
+            addr: None,
+            seed: 1,
+        }
+    }
+}
+
+/// What one client saw
+#[derive(Debug, Default)]
+struct ClientReport {
+    /// Since the bench started - `None` if the join was never acknowledged
+    joined_after: Option<Duration>,
+    frames: u64,
+    bytes: u64,
+    errors: u64,
+    sent: u64,
+    timed_out: u64,
+    /// Input until the views it caused reached us, for every input that got there
+    latencies: Vec<Duration>,
+    /// Connection ended before the bench did
+    dropped: bool,
+}
+
+/// Server memory from `/proc/<pid>/status` - in KiB
+#[derive(Debug, Clone, Copy, Default)]
+struct Memory {
+    idle: Option<u64>,
+    connected: Option<u64>,
+    peak: Option<u64>,
+    end: Option<u64>,
+}
+
+/// Everything the bench measured
+#[derive(Debug)]
+pub struct BenchReport {
+    game: String,
+    options: BenchOptions,
+    players: usize,
+    elapsed: Duration,
+    clients: Vec<ClientReport>,
+    memory: Memory,
+    /// (warnings, messages skipped) from the server's log - `None` for an external server
+    lagged: Option<(u64, u64)>,
+}
+
+/// Start a server (unless `options.addr` points at one), load it, and report
+pub async fn run<G: WebSocketGame>(options: &BenchOptions) -> Result<BenchReport, Box<dyn std::error::Error + Send + Sync>> {
+    let mut inputs = Vec::new();
+    for line in &options.inputs {
+        inputs.push(G::parse_line(line).ok_or_else(|| format!("{} can't parse the input '{}'", G::NAME, line))?);
+    }
+    if inputs.is_empty() && G::bot().is_none() {
+        return Err(format!("{} has no bot to pick moves - pass --input with a move to send", G::NAME).into());
+    }
+    let inputs = Arc::new(inputs);
+
+    let server = match &options.addr {
+        Some(addr) => Server::External(addr.clone()),
+        None => Server::spawn(G::NAME, options.seed).await?,
+    };
+    let url = format!("ws://{}", server.addr());
+
+    // Latency is timed off the `NotALobby` error a game server sends back - a lobby never sends it
+    let refused = match probe(&url).await {
+        Ok(ServerInfo::Game(game)) if game.name == G::NAME => None,
+        Ok(ServerInfo::Game(game)) => Some(format!("{} is hosting {}, not {}", server.addr(), game.name, G::NAME)),
+        Ok(ServerInfo::Lobby { .. }) => {
+            Some(format!("{} is a lobby - bench a single game started with `lanterm host --game {}`", server.addr(), G::NAME))
+        }
+        Err(e) => Some(e.to_string()),
+    };
+    if let Some(reason) = refused {
+        server.stop();
+        return Err(reason.into());
+    }
+
+    let mut memory = Memory { idle: server.rss(), ..Memory::default() };
+
+    let players = options.clients.min(G::MAX_PLAYERS);
+    let seated = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+    let deadline = started + options.duration;
+    let spawn_client = |index: usize| {
+        let client = BenchClient::<G> {
+            url: url.clone(),
+            index,
+            seat: (index < players).then_some(index),
+            slow: (index >= options.clients.saturating_sub(options.slow)).then_some(options.slow_delay),
+            inputs: Arc::clone(&inputs),
+            period: Duration::from_secs_f64(1.0 / options.rate.max(0.01)),
+            seed: options.seed.wrapping_add(index as u64),
+            seated: Arc::clone(&seated),
+            started,
+            deadline,
+            _game: std::marker::PhantomData,
+        };
+        tokio::spawn(client.run())
+    };
+
+    // Seat the players before the crowd arrives - a join storm can overflow their queues and
+    // skip the very frame that tells them they're in, and then nobody ever moves
+    let mut tasks: Vec<JoinHandle<ClientReport>> = (0..players).map(spawn_client).collect();
+    while seated.load(Ordering::Relaxed) < players && started.elapsed() < STARTUP_TIMEOUT {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    tasks.extend((players..options.clients).map(spawn_client));
+
+    // Everyone should be in well before the end - sample memory once they are
+    tokio::time::sleep(options.duration / 4).await;
+    memory.connected = server.rss();
+
+    let mut clients = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        clients.push(task.await.unwrap_or_default());
+    }
+    let elapsed = started.elapsed();
+    memory.end = server.rss();
+    memory.peak = server.peak_rss();
+
+    Ok(BenchReport {
+        game: G::NAME.to_string(),
+        options: options.clone(),
+        players,
+        elapsed,
+        clients,
+        memory,
+        lagged: server.stop(),
+    })
+}
+
+/// What's listening at `url` - one bare handshake, then the connection is dropped
+async fn probe(url: &str) -> Result<ServerInfo, Box<dyn std::error::Error + Send + Sync>> {
+    let (ws_stream, _) = tokio_tungstenite::connect_async(url).await
+        .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+    let welcome = protocol::client_handshake(&mut ws_sender, &mut ws_receiver, Hello::new(None)).await?;
+    Ok(welcome.server)
+}
+
+/// The server under test
+enum Server {
+    Spawned { addr: String, child: Child, log: std::thread::JoinHandle<(u64, u64)> },
+    External(String),
+}
+
+impl Server {
+    /// `lanterm host` on a free local port - its stdout is dropped, its stderr read for lag warnings
+    async fn spawn(game: &str, seed: u64) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        let addr = format!("127.0.0.1:{}", port);
+
+        let mut child = Command::new(std::env::current_exe()?)
+            .args(["host", "--game", game, "--addr", &addr, "--no-announce", "--seed", &seed.to_string()])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Couldn't start the server: {}", e))?;
+
+        // Read on a thread of its own - the server blocks on a full pipe, and a runtime busy with
+        // hundreds of clients would stall it and skew the numbers
+        let stderr = child.stderr.take().ok_or("Server has no stderr")?;
+        let log = std::thread::spawn(move || {
+            let (mut warnings, mut skipped) = (0, 0);
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if let Some(rest) = line.strip_prefix(LAGGED) {
+                    warnings += 1;
+                    skipped += rest.split_whitespace().next().and_then(|n| n.parse::<u64>().ok()).unwrap_or(0);
+                }
+            }
+            (warnings, skipped)
+        });
+
+        let started = Instant::now();
+        while tokio::net::TcpStream::connect(&addr).await.is_err() {
+            if started.elapsed() > STARTUP_TIMEOUT {
+                let _ = child.kill();
+                return Err(format!("Server didn't start listening on {}", addr).into());
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        Ok(Server::Spawned { addr, child, log })
+    }
+
+    fn addr(&self) -> &str {
+        match self {
+            Server::Spawned { addr, .. } | Server::External(addr) => addr,
+        }
+    }
+
+    fn rss(&self) -> Option<u64> {
+        self.status_field("VmRSS:")
+    }
+
+    /// High-water mark of the resident set - the kernel keeps it, so nothing is missed between samples
+    fn peak_rss(&self) -> Option<u64> {
+        self.status_field("VmHWM:")
+    }
+
+    fn status_field(&self, field: &str) -> Option<u64> {
+        let Server::Spawned { child, .. } = self else {
+            return None;
+        };
+        let status = std::fs::read_to_string(format!("/proc/{}/status", child.id())).ok()?;
+        let line = status.lines().find(|line| line.starts_with(field))?;
+        line.split_whitespace().nth(1)?.parse().ok()
+    }
+
+    /// Kill a spawned server and hand back its lag warnings
+    fn stop(self) -> Option<(u64, u64)> {
+        let Server::Spawned { mut child, log, .. } = self else {
+            return None;
+        };
+        let _ = child.kill();
+        let _ = child.wait();
+        log.join().ok()
+    }
+}
+
+/// One synthetic connection
+struct BenchClient<G: WebSocketGame> {
+    url: String,
+    index: usize,
+    /// Seat to take - `None` watches
+    seat: Option<usize>,
+    /// Pause after every frame - a reader that can't keep up
+    slow: Option<Duration>,
+    inputs: Arc<Vec<G::Input>>,
+    period: Duration,
+    seed: u64,
+    /// Counts seated clients whose join was acknowledged
+    seated: Arc<AtomicUsize>,
+    started: Instant,
+    deadline: Instant,
+    _game: std::marker::PhantomData<G>,
+}
+
+impl<G: WebSocketGame> BenchClient<G> {
+    async fn run(self) -> ClientReport {
+        let mut report = ClientReport::default();
+        let mut client = WebSocketGameClient::new(format!("bench{}", self.index));
+        if self.seat.is_none() {
+            client = client.spectate();
+        }
+        let Ok(client) = client.connect_headless::<G::View, G::Input>(&self.url).await else {
+            report.dropped = true;
+            return report;
+        };
+        let (mut sender, mut events) = client.split();
+
+        let mut bot = G::bot();
+        let mut rng = context::seeded_rng(self.seed);
+        let mut me = Player::nobody();
+        let mut view: Option<G::View> = None;
+        let mut last_input: Option<G::Input> = None;
+        let mut in_flight: Option<Instant> = None;
+        let mut ticker = tokio::time::interval(self.period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        let end = tokio::time::sleep_until(self.deadline);
+        tokio::pin!(end);
+        loop {
+            tokio::select! {
+                _ = &mut end => break,
+
+                event = events.next() => {
+                    let Some(event) = event else {
+                        report.dropped = true;
+                        break;
+                    };
+                    report.frames += 1;
+                    report.bytes += protocol::encode(&event).len() as u64;
+                    match event {
+                        ServerMessage::Joined { player_id, name, .. } => {
+                            report.joined_after = Some(self.started.elapsed());
+                            self.seated.fetch_add(1, Ordering::Relaxed);
+                            let role = if self.seat == Some(0) { PlayerRole::Host } else { PlayerRole::Player };
+                            me = Player { id: player_id, name, seat: self.seat, role };
+                        }
+                        ServerMessage::Spectating => report.joined_after = Some(self.started.elapsed()),
+                        ServerMessage::StateUpdate(update) => {
+                            // The ack itself may be one of the frames a lagging queue skipped
+                            report.joined_after.get_or_insert_with(|| self.started.elapsed());
+                            view = Some(update);
+                        }
+                        ServerMessage::Error(err) if err.code == ErrorCode::NotALobby => {
+                            if let Some(sent_at) = in_flight.take() {
+                                report.latencies.push(sent_at.elapsed());
+                            }
+                        }
+                        ServerMessage::Error(_) => report.errors += 1,
+                        _ => {}
+                    }
+                    if let Some(delay) = self.slow {
+                        tokio::time::sleep(delay).await;
+                    }
+                }
+
+                _ = ticker.tick(), if self.seat.is_some() && report.joined_after.is_some() => {
+                    if let Some(sent_at) = in_flight {
+                        if sent_at.elapsed() < INPUT_TIMEOUT {
+                            continue;
+                        }
+                        report.timed_out += 1;
+                        in_flight = None;
+                    }
+
+                    // Scripted lines in turn, else the bot's move - and when it has none (not our
+                    // turn, game over) the last move again, which the game refuses but still answers
+                    let input = if self.inputs.is_empty() {
+                        let chosen = match (&mut bot, &view) {
+                            (Some(bot), Some(view)) => bot.choose(view, &me, &mut rng),
+                            _ => None,
+                        };
+                        chosen.or_else(|| last_input.clone())
+                    } else {
+                        Some(self.inputs[report.sent as usize % self.inputs.len()].clone())
+                    };
+                    let Some(input) = input else { continue };
+
+                    // Other players' moves also send us state updates, so the next one isn't
+                    // necessarily ours. A single-game server answers `ListRooms` to us alone, from
+                    // the same queue, after the views our input caused - that reply ends the timing
+                    let marked = match sender.send_input(input.clone()).await {
+                        Ok(()) => sender.send(&ClientMessage::ListRooms).await,
+                        Err(e) => Err(e),
+                    };
+                    if marked.is_err() {
+                        report.dropped = true;
+                        break;
+                    }
+                    report.sent += 1;
+                    last_input = Some(input);
+                    in_flight = Some(Instant::now());
+                }
+            }
+        }
+
+        if !report.dropped {
+            let _ = sender.leave().await;
+        }
+        report
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let clients = self.clients.len();
+        let secs = self.elapsed.as_secs_f64().max(0.001);
+        let sum = |field: fn(&ClientReport) -> u64| self.clients.iter().map(field).sum::<u64>();
+
+        writeln!(
+            f,
+            "📊 {} bench - {} clients ({} playing, {} watching, {} slow) for {:.1}s",
+            self.game, clients, self.players, clients - self.players, self.options.slow.min(clients), secs
+        )?;
+
+        let joined: Vec<Duration> = self.clients.iter().filter_map(|c| c.joined_after).collect();
+        let last_join = joined.iter().max().copied().unwrap_or_default();
+        writeln!(f, "   Joined     {}/{} ({:.0}ms until the last one was in)", joined.len(), clients, ms(last_join))?;
+
+        let (sent, timed_out) = (sum(|c| c.sent), sum(|c| c.timed_out));
+        writeln!(f, "   Inputs     {} sent, {} timed out - {:.1}/s", sent, timed_out, sent as f64 / secs)?;
+
+        let mut latencies: Vec<Duration> = self.clients.iter().flat_map(|c| c.latencies.iter().copied()).collect();
+        latencies.sort();
+        if latencies.is_empty() {
+            writeln!(f, "   Latency    no inputs answered")?;
+        } else {
+            let at = |p: f64| ms(latencies[((latencies.len() - 1) as f64 * p).round() as usize]);
+            writeln!(
+                f,
+                "   Latency    p50 {:.2}ms  p90 {:.2}ms  p99 {:.2}ms  max {:.2}ms  (input until its views are out)",
+                at(0.5), at(0.9), at(0.99), at(1.0)
+            )?;
+        }
+
+        let (frames, bytes) = (sum(|c| c.frames), sum(|c| c.bytes));
+        writeln!(
+            f,
+            "   Delivered  {} frames, {:.1} MB - {:.0} frames/s, {:.2} MB/s",
+            frames, mb(bytes), frames as f64 / secs, mb(bytes) / secs
+        )?;
+
+        let dropped = self.clients.iter().filter(|c| c.dropped).count();
+        writeln!(f, "   Problems   {} error frames, {} connections dropped", sum(|c| c.errors), dropped)?;
+
+        match self.lagged {
+            Some((0, _)) => writeln!(f, "   Lagged     none - every connection kept up")?,
+            Some((warnings, skipped)) => writeln!(f, "   Lagged     {} messages skipped ({} times a queue overflowed)", skipped, warnings)?,
+            None => writeln!(f, "   Lagged     unknown - only counted for a server the bench starts")?,
+        }
+
+        let kib = |value: Option<u64>| value.map(|k| format!("{:.1} MB", k as f64 / 1024.0)).unwrap_or_else(|| "n/a".to_string());
+        write!(
+            f,
+            "   Memory     {} idle, {} connected, {} peak, {} at the end",
+            kib(self.memory.idle), kib(self.memory.connected), kib(self.memory.peak), kib(self.memory.end)
+        )?;
+        if let (Some(idle), Some(connected)) = (self.memory.idle, self.memory.connected) {
+            if clients > 0 {
+                write!(f, " (~{:.0} KB per connection)", connected.saturating_sub(idle) as f64 / clients as f64)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn mb(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
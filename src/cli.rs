@@ -1,6 +1,22 @@
+use std::path::PathBuf;
+
 use clap::{Parser, Subcommand};
-use dialoguer::{theme::ColorfulTheme, Select};
+use dialoguer::{theme::ColorfulTheme, Confirm, Select};
+use tokio::time::Duration;
 
+use crate::bench::BenchOptions;
+use crate::core::discovery::{self, DiscoveredServer};
+use crate::core::lobby::LobbyServer;
+use crate::client::headless::ScriptOptions;
+use crate::client::replay::ReplayOptions;
+use crate::client::websocket_client::JoinOptions;
+use crate::core::context::Settings;
+use crate::core::input::KeyChord;
+use crate::core::protocol::Role;
+use crate::core::registry::{GameMetadata, GameRegistry};
+use crate::core::save::SavedRoom;
+use crate::core::settings::SettingKind;
+use crate::core::websocket_host::HostOptions;
 use crate::games::create_default_registry;
 
 #[derive(Parser)]
@@ -23,6 +39,42 @@ pub enum Commands {
         /// Game to host (if not specified, will show selection)
         #[arg(short, long)]
         game: Option<String>,
+        
+        /// Host a lobby where players create and join rooms for any game
+        #[arg(long, conflicts_with = "game")]
+        lobby: bool,
+        
+        /// Don't announce this server on the LAN
+        #[arg(long)]
+        no_announce: bool,
+        
+        /// Seconds to hold a dropped player's seat for them to reconnect
+        #[arg(long, default_value_t = 30)]
+        reconnect_grace: u64,
+        
+        /// Seed the game's RNG to replay a game exactly (default: random, printed at startup)
+        #[arg(long)]
+        seed: Option<u64>,
+        
+        /// Record the session to a JSON Lines file for `lanterm replay` (lobbies add the room id)
+        #[arg(long, value_name = "FILE")]
+        record: Option<PathBuf>,
+        
+        /// Pick up a saved game - players rejoin with their old names (the game comes from the save)
+        #[arg(long, value_name = "FILE", conflicts_with = "lobby")]
+        resume: Option<PathBuf>,
+        
+        /// Where `/save`, SIGUSR1 and Ctrl-C save the game (default: the --resume file)
+        #[arg(long, value_name = "FILE")]
+        save: Option<PathBuf>,
+        
+        /// Seat this many bots in every new room - try a 2-player game on your own
+        #[arg(long, default_value_t = 0)]
+        bots: usize,
+        
+        /// Game option like "board=12" - repeat for several (a resumed game keeps its own)
+        #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_setting, conflicts_with_all = ["lobby", "resume"])]
+        settings: Vec<(String, String)>,
     },
     /// Join a game server
     Join {
@@ -32,6 +84,117 @@ pub enum Commands {
         /// Player name
         #[arg(short, long, default_value = "Player")]
         name: String,
+        
+        /// Key chord that quits, e.g. "ctrl-q" or "f10" - repeat for several (default: the game's, usually Ctrl-C/Esc)
+        #[arg(long = "quit-key", value_name = "CHORD")]
+        quit_keys: Vec<KeyChord>,
+        
+        /// No terminal UI - read moves from stdin and print server events as JSON lines (see bot-client)
+        #[arg(long)]
+        headless: bool,
+    },
+    /// Watch a game without taking a seat
+    Watch {
+        /// Server address to connect to (e.g., "127.0.0.1:4000")
+        addr: String,
+        
+        /// Name shown to players
+        #[arg(short, long, default_value = "Spectator")]
+        name: String,
+        
+        /// Key chord that quits, e.g. "ctrl-q" or "f10" - repeat for several
+        #[arg(long = "quit-key", value_name = "CHORD")]
+        quit_keys: Vec<KeyChord>,
+    },
+    /// Play from a script without a terminal - moves from stdin or a file, server events out as JSON lines
+    BotClient {
+        /// Server address to connect to (e.g., "127.0.0.1:4000")
+        addr: String,
+        
+        /// Player name
+        #[arg(short, long, default_value = "Bot")]
+        name: String,
+        
+        /// Read moves from this file instead of stdin - also takes :chat, :sleep MS, :wait TEXT and :quit
+        #[arg(long, value_name = "FILE")]
+        script: Option<PathBuf>,
+        
+        /// Lobby room to join - required when the server is a lobby
+        #[arg(long)]
+        room: Option<String>,
+        
+        /// Watch instead of taking a seat
+        #[arg(long)]
+        watch: bool,
+        
+        /// Milliseconds to keep printing events after the last line before leaving
+        #[arg(long, default_value_t = 1000)]
+        linger: u64,
+    },
+    /// Find lanterm servers on the LAN
+    Discover {
+        /// Seconds to listen for server announcements
+        #[arg(short, long, default_value_t = 2)]
+        timeout: u64,
+    },
+    /// Play back a recorded session
+    Replay {
+        /// Recording written by `lanterm host --record`
+        file: PathBuf,
+        
+        /// Playback speed - 2 is twice as fast
+        #[arg(short, long, default_value_t = 1.0)]
+        speed: f64,
+        
+        /// Watch as this player instead of as a spectator
+        #[arg(long = "as", value_name = "PLAYER")]
+        viewer: Option<String>,
+        
+        /// Start paused on the opening state
+        #[arg(long)]
+        paused: bool,
+        
+        /// Re-run every input from the seed and check the states match instead of playing
+        #[arg(long)]
+        verify: bool,
+    },
+    /// Load-test a server with synthetic clients - latency, throughput, memory and lag
+    Bench {
+        /// Game to bench (if not specified, will show selection)
+        #[arg(short, long)]
+        game: Option<String>,
+        
+        /// Connections to open - the first ones take the seats, the rest watch
+        #[arg(short, long, default_value_t = 50)]
+        clients: usize,
+        
+        /// Seconds to keep sending inputs
+        #[arg(short, long, default_value_t = 10)]
+        duration: u64,
+        
+        /// Inputs per second from each seated client
+        #[arg(short, long, default_value_t = 10.0)]
+        rate: f64,
+        
+        /// Make this many clients slow readers - they pause after every frame
+        #[arg(long, default_value_t = 0)]
+        slow: usize,
+        
+        /// How long slow readers pause, in milliseconds
+        #[arg(long, default_value_t = 20)]
+        slow_ms: u64,
+        
+        /// Move to send instead of asking the game's bot - repeat to cycle through several
+        #[arg(long = "input", value_name = "LINE")]
+        inputs: Vec<String>,
+        
+        /// Bench a running server instead of starting one (memory and lag aren't measured)
+        #[arg(long)]
+        addr: Option<String>,
+        
+        /// Seed for the server and the bots
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
     },
     /// List available games
     List,
@@ -41,10 +204,30 @@ pub async fn run_cli() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let cli = Cli::parse();
 
     match cli.command {
-        Some(Commands::Host { addr, game }) => {
+        Some(Commands::Host { addr, lobby: true, no_announce, reconnect_grace, seed, record, save, bots, .. }) => {
+            let options = HostOptions {
+                announce: !no_announce,
+                reconnect_grace: Duration::from_secs(reconnect_grace),
+                seed,
+                record,
+                resume: None,
+                save,
+                bots,
+                settings: Settings::new(),
+            };
+            start_lobby(&addr, options).await?;
+        }
+        
+        Some(Commands::Host { addr, game, no_announce, reconnect_grace, seed, record, resume, save, bots, settings, .. }) => {
             let registry = create_default_registry();
             
-            let game_name = if let Some(game_name) = game {
+            // A save already knows its game
+            let game = match (game, &resume) {
+                (None, Some(path)) => Some(SavedRoom::load(path)?.game),
+                (game, _) => game,
+            };
+            
+            let (game_name, settings) = if let Some(game_name) = game {
                 if !registry.has_game(&game_name) {
                     eprintln!("❌ Game '{}' not found", game_name);
                     eprintln!("Available games:");
@@ -53,21 +236,106 @@ pub async fn run_cli() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                     }
                     std::process::exit(1);
                 }
-                game_name
+                (game_name, settings.into_iter().collect())
             } else {
-                select_game(&registry)?
+                // Picked from the menu - ask for the settings too, unless --set already gave some
+                let game_name = select_game(&registry)?;
+                let settings = if settings.is_empty() { choose_settings(&registry, &game_name)? } else { settings.into_iter().collect() };
+                (game_name, settings)
+            };
+            
+            let settings = match registry.resolve_settings(&game_name, &settings) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    eprintln!("❌ {}", e);
+                    if let Some(metadata) = registry.list_games().into_iter().find(|g| g.name == game_name) {
+                        print_settings(&metadata);
+                    }
+                    std::process::exit(1);
+                }
+            };
+            
+            let options = HostOptions {
+                announce: !no_announce,
+                reconnect_grace: Duration::from_secs(reconnect_grace),
+                seed,
+                record,
+                resume,
+                save,
+                bots,
+                settings,
             };
             
             println!("🚀 Starting {} server on {}", game_name, addr);
-            registry.start_game(&game_name, &addr).await?;
+            registry.start_game(&game_name, &addr, options).await?;
+        }
+        
+        Some(Commands::Join { addr, name, headless: true, .. }) => {
+            let registry = create_default_registry();
+            registry.headless_join(&addr, name, JoinOptions::default(), &ScriptOptions::default()).await?;
         }
         
-        Some(Commands::Join { addr, name }) => {
+        Some(Commands::Join { addr, name, quit_keys, .. }) => {
             println!("🔗 Connecting to {} as '{}'...", addr, name);
             
             // Auto-detect game type from server metadata
             let registry = create_default_registry();
-            registry.auto_detect_and_join(&addr, name).await?;
+            registry.auto_detect_and_join(&addr, name, JoinOptions { quit_keys, ..Default::default() }).await?;
+        }
+        
+        Some(Commands::Watch { addr, name, quit_keys }) => {
+            println!("👀 Watching {} as '{}'...", addr, name);
+            
+            let registry = create_default_registry();
+            registry.auto_detect_and_join(&addr, name, JoinOptions { role: Role::Spectator, quit_keys, ..Default::default() }).await?;
+        }
+        
+        Some(Commands::BotClient { addr, name, script, room, watch, linger }) => {
+            // Nothing else goes to stdout - it's for the event stream
+            let registry = create_default_registry();
+            let role = if watch { Role::Spectator } else { Role::Player };
+            let script = ScriptOptions { script, linger: Duration::from_millis(linger) };
+            registry.headless_join(&addr, name, JoinOptions { room, role, ..Default::default() }, &script).await?;
+        }
+        
+        Some(Commands::Discover { timeout }) => {
+            println!("📡 Listening for lanterm servers for {}s...", timeout);
+            let servers = discovery::discover(Duration::from_secs(timeout)).await?;
+            
+            if servers.is_empty() {
+                println!("No servers found. Is anyone hosting on this network?");
+            }
+            for server in &servers {
+                println!("🖥️  {}", describe_server(server));
+                println!("   {}", server.announcement.description);
+                println!();
+            }
+        }
+        
+        Some(Commands::Replay { file, speed, viewer, paused, verify }) => {
+            let registry = create_default_registry();
+            registry.replay(&file, &ReplayOptions { speed, viewer, paused, verify })?;
+        }
+        
+        Some(Commands::Bench { game, clients, duration, rate, slow, slow_ms, inputs, addr, seed }) => {
+            let registry = create_default_registry();
+            let game_name = match game {
+                Some(game_name) => game_name,
+                None => select_game(&registry)?,
+            };
+            let options = BenchOptions {
+                clients,
+                duration: Duration::from_secs(duration),
+                rate,
+                slow,
+                slow_delay: Duration::from_millis(slow_ms),
+                inputs,
+                addr,
+                seed,
+            };
+            
+            println!("⏱️  Benching {} with {} clients for {}s...", game_name, clients, duration);
+            registry.bench(&game_name, &options).await?;
         }
         
         Some(Commands::List) => {
@@ -76,10 +344,7 @@ pub async fn run_cli() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
             println!();
             
             for metadata in registry.list_games() {
-                println!("📦 {}", metadata.name);
-                println!("   {}", metadata.description);
-                println!("   Players: {}-{}", metadata.min_players, metadata.max_players);
-                println!();
+                print_game(&metadata);
             }
         }
         
@@ -92,7 +357,15 @@ pub async fn run_cli() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     Ok(())
 }
 
-fn select_game(registry: &crate::core::registry::GameRegistry) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+/// `key=value` from `--set`
+fn parse_setting(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.trim().to_string())),
+        _ => Err(format!("'{}' should look like key=value", arg)),
+    }
+}
+
+fn select_game(registry: &GameRegistry) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let games = registry.list_games();
     
     if games.is_empty() {
@@ -107,6 +380,115 @@ fn select_game(registry: &crate::core::registry::GameRegistry) -> Result<String,
     Ok(games[selection].name.clone())
 }
 
+/// Ask for each of the game's settings - a single "no" keeps them all at their defaults
+/// Values are checked as they're typed; a combination the game turns down is asked for again
+fn choose_settings(registry: &GameRegistry, game_name: &str) -> Result<Settings, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(metadata) = registry.list_games().into_iter().find(|g| g.name == game_name) else {
+        return Err(format!("Game '{}' not found", game_name).into());
+    };
+    if metadata.settings.is_empty() {
+        return Ok(Settings::new());
+    }
+
+    let theme = ColorfulTheme::default();
+    let change = Confirm::with_theme(&theme)
+        .with_prompt(format!("⚙️  Change {}'s settings?", game_name))
+        .default(false)
+        .interact()?;
+    if !change {
+        return Ok(Settings::new());
+    }
+
+    loop {
+        let mut settings = Settings::new();
+        for spec in metadata.settings {
+            let prompt = format!("{} - {}", spec.name, spec.help);
+            let value = match spec.kind {
+                SettingKind::Switch => {
+                    let on = Confirm::with_theme(&theme).with_prompt(prompt).default(spec.default == "on").interact()?;
+                    if on { "on" } else { "off" }.to_string()
+                }
+                SettingKind::Number { .. } | SettingKind::Text => dialoguer::Input::<String>::with_theme(&theme)
+                    .with_prompt(format!("{} ({})", prompt, spec.kind))
+                    .default(spec.default.to_string())
+                    .show_default(!spec.default.is_empty())
+                    .allow_empty(true)
+                    .validate_with(|value: &String| spec.check(value).map(|_| ()))
+                    .interact_text()?,
+            };
+            settings.insert(spec.name.to_string(), value);
+        }
+
+        match registry.resolve_settings(game_name, &settings) {
+            Ok(settings) => return Ok(settings),
+            Err(e) => eprintln!("❌ {} - let's try that again", e),
+        }
+    }
+}
+
+/// One entry of `lanterm list`
+fn print_game(metadata: &GameMetadata) {
+    println!("📦 {}", metadata.name);
+    println!("   {}", metadata.description);
+    println!("   Players: {}-{}", metadata.min_players, metadata.max_players);
+    print_settings(metadata);
+    println!();
+}
+
+fn print_settings(metadata: &GameMetadata) {
+    if metadata.settings.is_empty() {
+        return;
+    }
+    println!("   Settings (--set key=value):");
+    for spec in metadata.settings {
+        println!("     • {}", spec.describe());
+    }
+}
+
+async fn start_lobby(addr: &str, options: HostOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    println!("🏢 Starting lobby server on {}", addr);
+    let mut server = LobbyServer::new(addr, create_default_registry(), options).await?;
+    server.run().await
+}
+
+fn describe_server(server: &DiscoveredServer) -> String {
+    let announcement = &server.announcement;
+    let watching = if announcement.spectators > 0 {
+        format!(", {} watching", announcement.spectators)
+    } else {
+        String::new()
+    };
+    format!(
+        "{} v{} at {} ({} players{})",
+        announcement.name, announcement.version, server.addr, announcement.players, watching
+    )
+}
+
+/// Pick a server found on the LAN, falling back to typing an address
+async fn select_server() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    println!("📡 Looking for games on the LAN...");
+    let servers = discovery::discover(Duration::from_millis(1500)).await.unwrap_or_default();
+
+    let mut items: Vec<String> = servers.iter().map(describe_server).collect();
+    items.push("✏️  Enter an address manually".to_string());
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("🔗 Join which server?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    if let Some(server) = servers.get(selection) {
+        return Ok(server.addr.to_string());
+    }
+
+    let addr = dialoguer::Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Server address")
+        .default("127.0.0.1:4000".to_string())
+        .interact_text()?;
+    Ok(addr)
+}
+
 async fn show_main_menu() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     println!("🕹️  Welcome to Lanterm!");
     println!("   A Rust framework for multiplayer terminal games");
@@ -114,7 +496,9 @@ async fn show_main_menu() -> Result<(), Box<dyn std::error::Error + Send + Sync>
 
     let options = vec![
         "🏠 Host a game",
+        "🏢 Host a lobby (many rooms)",
         "🔗 Join a game",
+        "👀 Watch a game",
         "📋 List available games",
         "🚪 Exit",
     ];
@@ -129,6 +513,7 @@ async fn show_main_menu() -> Result<(), Box<dyn std::error::Error + Send + Sync>
             // Host a game
             let registry = create_default_registry();
             let game_name = select_game(&registry)?;
+            let settings = choose_settings(&registry, &game_name)?;
             
             let addr = dialoguer::Input::with_theme(&ColorfulTheme::default())
                 .with_prompt("Server address")
@@ -136,15 +521,21 @@ async fn show_main_menu() -> Result<(), Box<dyn std::error::Error + Send + Sync>
                 .interact_text()?;
 
             println!("🚀 Starting {} server on {}", game_name, addr);
-            registry.start_game(&game_name, &addr).await?;
+            registry.start_game(&game_name, &addr, HostOptions { settings, ..Default::default() }).await?;
         }
         1 => {
-            // Join a game
-            let addr = dialoguer::Input::with_theme(&ColorfulTheme::default())
+            // Host a lobby
+            let addr: String = dialoguer::Input::with_theme(&ColorfulTheme::default())
                 .with_prompt("Server address")
-                .default("127.0.0.1:4000".to_string())
+                .default("0.0.0.0:4000".to_string())
                 .interact_text()?;
 
+            start_lobby(&addr, HostOptions::default()).await?;
+        }
+        2 => {
+            // Join a game - servers on the LAN are offered first
+            let addr = select_server().await?;
+
             let name = dialoguer::Input::with_theme(&ColorfulTheme::default())
                 .with_prompt("Your name")
                 .default("Player".to_string())
@@ -154,9 +545,19 @@ async fn show_main_menu() -> Result<(), Box<dyn std::error::Error + Send + Sync>
             
             // Auto-detect game from server metadata
             let registry = create_default_registry();
-            registry.auto_detect_and_join(&addr, name).await?;
+            registry.auto_detect_and_join(&addr, name, JoinOptions::default()).await?;
         }
-        2 => {
+        3 => {
+            // Watch a game - same server picker, no seat taken
+            let addr = select_server().await?;
+
+            println!("👀 Watching {}...", addr);
+            
+            let registry = create_default_registry();
+            let options = JoinOptions { role: Role::Spectator, ..Default::default() };
+            registry.auto_detect_and_join(&addr, "Spectator".to_string(), options).await?;
+        }
+        4 => {
             // List games
             let registry = create_default_registry();
             println!();
@@ -164,13 +565,10 @@ async fn show_main_menu() -> Result<(), Box<dyn std::error::Error + Send + Sync>
             println!();
             
             for metadata in registry.list_games() {
-                println!("📦 {}", metadata.name);
-                println!("   {}", metadata.description);
-                println!("   Players: {}-{}", metadata.min_players, metadata.max_players);
-                println!();
+                print_game(&metadata);
             }
         }
-        3 => {
+        5 => {
             // Exit
             println!("👋 Goodbye!");
         }
@@ -0,0 +1,89 @@
+/// Chat pane - drawn by the framework under the game, never seen by `GameRenderer`
+use std::collections::VecDeque;
+
+use crate::core::protocol::{ChatMessage, Role};
+use crate::core::terminal::{TerminalContext, TerminalColor};
+
+/// Chat lines kept on screen below the game
+const VISIBLE_LINES: usize = 6;
+
+/// Scrollback the client keeps around
+const SCROLLBACK: usize = 200;
+
+/// Client-side chat state - scrollback plus the line being typed
+#[derive(Debug, Default)]
+pub struct ChatPane {
+    messages: VecDeque<ChatMessage>,
+    /// `Some` while in chat mode - keys go here instead of the game
+    draft: Option<String>,
+}
+
+impl ChatPane {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, message: ChatMessage) {
+        if self.messages.len() == SCROLLBACK {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+    }
+
+    /// Replace everything with the server's history - sent again after every (re)join
+    pub fn replace(&mut self, history: Vec<ChatMessage>) {
+        self.messages = history.into();
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.draft.is_some()
+    }
+
+    /// Enter chat mode with an empty line
+    pub fn start(&mut self) {
+        self.draft = Some(String::new());
+    }
+
+    /// Leave chat mode without sending
+    pub fn cancel(&mut self) {
+        self.draft = None;
+    }
+
+    pub fn type_char(&mut self, c: char) {
+        if let Some(draft) = self.draft.as_mut() {
+            draft.push(c);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(draft) = self.draft.as_mut() {
+            draft.pop();
+        }
+    }
+
+    /// Leave chat mode and hand back what was typed
+    pub fn take(&mut self) -> Option<String> {
+        self.draft.take().filter(|text| !text.trim().is_empty())
+    }
+
+    /// Line being typed - `None` outside chat mode
+    pub fn draft(&self) -> Option<&str> {
+        self.draft.as_deref()
+    }
+
+    /// Draw recent chat below the game - the layout owns the prompt line
+    pub fn render(&self, ctx: &mut TerminalContext) {
+        if self.messages.is_empty() {
+            return;
+        }
+        ctx.empty_line();
+        ctx.print_colored_line("─── 💬 chat ───", TerminalColor::Cyan);
+
+        let skip = self.messages.len().saturating_sub(VISIBLE_LINES);
+        for message in self.messages.iter().skip(skip) {
+            let tag = if message.role == Role::Spectator { "👀 " } else { "" };
+            ctx.print_colored(&format!("[{}] {}{}: ", message.sent_at.format("%H:%M"), tag, message.from), TerminalColor::Cyan);
+            ctx.print_line(&message.text);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+/// Headless client - typed server events in, inputs out, no terminal
+///
+/// `HeadlessClient` is the library side: connect with `WebSocketGameClient::connect_headless` and
+/// read `ServerMessage`s while sending inputs. `run_script` is what `lanterm bot-client` runs on top of
+/// it - lines from a script or stdin go to the game, and every server event comes out as a JSON line.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+use futures_util::{SinkExt, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::client::websocket_client::{WsReceiver, WsSender};
+use crate::core::protocol::{self, ClientMessage, ErrorCode, ProtocolError, ServerMessage};
+
+/// Every frame the server sends, decoded - ends when the connection closes
+pub type EventStream<View> = Pin<Box<dyn Stream<Item = ServerMessage<View>> + Send>>;
+
+/// How `run_script` feeds the game - built from the `lanterm bot-client` flags
+#[derive(Debug, Clone)]
+pub struct ScriptOptions {
+    /// Read lines from this file - default is stdin
+    pub script: Option<PathBuf>,
+    /// Keep printing events this long after the last line before leaving
+    pub linger: Duration,
+}
+
+impl Default for ScriptOptions {
+    fn default() -> Self {
+        Self {
+            script: None,
+            linger: Duration::from_secs(1),
+        }
+    }
+}
+
+/// The sending half - inputs, chat and leaving
+pub struct HeadlessSender<Input> {
+    ws_sender: WsSender,
+    _input: PhantomData<fn(Input)>,
+}
+
+impl<Input: Serialize> HeadlessSender<Input> {
+    /// Send one input to the game - same as a player typing it
+    pub async fn send_input(&mut self, input: Input) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.send(&ClientMessage::PlayerInput(input)).await
+    }
+
+    /// Say something in the room chat - `/` commands like `/bot` work here too
+    pub async fn chat(&mut self, text: impl Into<String>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.send(&ClientMessage::Chat(text.into())).await
+    }
+
+    /// Give up the seat and close the connection
+    pub async fn leave(mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.send(&ClientMessage::PlayerLeave).await?;
+        self.ws_sender.close().await?;
+        Ok(())
+    }
+
+    pub(crate) async fn send(&mut self, frame: &ClientMessage<Input>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.ws_sender.send(Message::Text(protocol::encode(frame))).await?;
+        Ok(())
+    }
+}
+
+/// A joined (or watching) connection without a screen - for bots, tests and load generators
+pub struct HeadlessClient<View, Input> {
+    sender: HeadlessSender<Input>,
+    events: EventStream<View>,
+}
+
+impl<View, Input> HeadlessClient<View, Input>
+where
+    View: DeserializeOwned + Send + 'static,
+    Input: Serialize,
+{
+    pub(crate) fn new(ws_sender: WsSender, ws_receiver: WsReceiver) -> Self {
+        Self {
+            sender: HeadlessSender { ws_sender, _input: PhantomData },
+            events: decode_events(ws_receiver),
+        }
+    }
+
+    /// Next frame from the server - `None` once the connection is gone
+    pub async fn next_event(&mut self) -> Option<ServerMessage<View>> {
+        self.events.next().await
+    }
+
+    pub async fn send_input(&mut self, input: Input) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.sender.send_input(input).await
+    }
+
+    pub async fn chat(&mut self, text: impl Into<String>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.sender.chat(text).await
+    }
+
+    pub async fn leave(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.sender.leave().await
+    }
+
+    /// Split into a sender and an event stream - read and write from different tasks
+    pub fn split(self) -> (HeadlessSender<Input>, EventStream<View>) {
+        (self.sender, self.events)
+    }
+}
+
+/// Text frames as typed events - a frame that doesn't fit the view becomes a `MalformedFrame` error
+fn decode_events<View: DeserializeOwned + Send + 'static>(ws_receiver: WsReceiver) -> EventStream<View> {
+    futures_util::stream::unfold(ws_receiver, |mut ws_receiver| async move {
+        loop {
+            match ws_receiver.next().await? {
+                Ok(Message::Text(text)) => {
+                    let event = serde_json::from_str(&text).unwrap_or_else(|e| {
+                        ServerMessage::Error(ProtocolError::new(ErrorCode::MalformedFrame, format!("Couldn't read server frame: {}", e)))
+                    });
+                    return Some((event, ws_receiver));
+                }
+                Ok(Message::Close(_)) | Err(_) => return None,
+                Ok(_) => {}
+            }
+        }
+    })
+    .boxed()
+}
+
+/// One line of a script
+enum Command<Input> {
+    Input(Input),
+    Chat(String),
+    Sleep(Duration),
+    /// Hold the script until an event containing this text arrives
+    Wait(String),
+    Quit,
+}
+
+impl<Input: DeserializeOwned> Command<Input> {
+    /// `None` for blanks and `#` comments
+    /// Lines starting with `:` are directives, `{` is a raw JSON input, anything else goes to `parse_line`
+    fn parse<I: FnMut(&str) -> Option<Input>>(line: &str, input_fn: &mut I) -> Option<Result<Self, String>> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let Some(directive) = line.strip_prefix(':') else {
+            let input = if line.starts_with('{') {
+                serde_json::from_str(line).map_err(|e| format!("'{}' isn't a valid input: {}", line, e))
+            } else {
+                input_fn(line).ok_or_else(|| format!("Couldn't understand '{}'", line))
+            };
+            return Some(input.map(Command::Input));
+        };
+
+        let (name, arg) = directive.split_once(' ').map(|(n, a)| (n, a.trim())).unwrap_or((directive, ""));
+        Some(match name {
+            "chat" => Ok(Command::Chat(arg.to_string())),
+            "sleep" => arg.parse().map(|ms| Command::Sleep(Duration::from_millis(ms))).map_err(|_| format!("':sleep' wants milliseconds, got '{}'", arg)),
+            "wait" if !arg.is_empty() => Ok(Command::Wait(arg.to_string())),
+            "wait" => Err("':wait' needs some text to wait for".to_string()),
+            "quit" => Ok(Command::Quit),
+            _ => Err(format!("Unknown directive ':{}' - try :chat, :sleep, :wait or :quit", name)),
+        })
+    }
+}
+
+/// Where the script is - running the next line, or held by a sleep, a wait or the final linger
+enum Step {
+    Ready,
+    Sleeping(Instant),
+    Waiting(String),
+    Lingering(Instant),
+}
+
+/// Play a script against the server - every event is printed to stdout as one JSON line
+/// Problems with the script go to stderr so stdout stays machine-readable
+pub async fn run_script<View, Input, I>(client: HeadlessClient<View, Input>, options: &ScriptOptions, mut input_fn: I) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+where
+    View: Serialize + DeserializeOwned + Send + 'static,
+    Input: Serialize + DeserializeOwned,
+    I: FnMut(&str) -> Option<Input>,
+{
+    let mut lines = read_lines(options.script.clone())?;
+    let (mut sender, mut events) = client.split();
+
+    let mut step = Step::Ready;
+    let mut number = 0;
+    // Events since the last thing we sent - `:wait` looks here first so a fast reply isn't missed
+    let mut since_sent: Vec<String> = Vec::new();
+
+    loop {
+        let deadline = match step {
+            Step::Sleeping(at) | Step::Lingering(at) => at,
+            _ => Instant::now() + Duration::from_secs(3600),
+        };
+
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else {
+                    eprintln!("🔌 Server closed the connection");
+                    return Ok(());
+                };
+                let json = protocol::encode(&event);
+                println!("{}", json);
+                if matches!(&step, Step::Waiting(needle) if json.contains(needle.as_str())) {
+                    step = Step::Ready;
+                }
+                since_sent.push(json);
+            }
+
+            _ = tokio::time::sleep_until(deadline), if matches!(step, Step::Sleeping(_) | Step::Lingering(_)) => {
+                if matches!(step, Step::Lingering(_)) {
+                    return sender.leave().await;
+                }
+                step = Step::Ready;
+            }
+
+            line = lines.recv(), if matches!(step, Step::Ready) => {
+                let Some(line) = line else {
+                    step = Step::Lingering(Instant::now() + options.linger);
+                    continue;
+                };
+                number += 1;
+
+                match Command::parse(&line, &mut input_fn) {
+                    None => {}
+                    Some(Err(e)) => eprintln!("⚠️  Line {}: {}", number, e),
+                    Some(Ok(Command::Input(input))) => {
+                        since_sent.clear();
+                        sender.send_input(input).await?;
+                    }
+                    Some(Ok(Command::Chat(text))) => {
+                        since_sent.clear();
+                        sender.chat(text).await?;
+                    }
+                    Some(Ok(Command::Sleep(by))) => step = Step::Sleeping(Instant::now() + by),
+                    Some(Ok(Command::Wait(needle))) if !since_sent.iter().any(|json| json.contains(needle.as_str())) => {
+                        step = Step::Waiting(needle);
+                    }
+                    Some(Ok(Command::Wait(_))) => {}
+                    Some(Ok(Command::Quit)) => return sender.leave().await,
+                }
+            }
+        }
+    }
+}
+
+/// Lines from the script or stdin, read on their own thread
+/// A blocking read can't be cancelled, so it lives outside the runtime and never holds up exit
+fn read_lines(script: Option<PathBuf>) -> Result<mpsc::UnboundedReceiver<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let reader: Box<dyn BufRead + Send> = match &script {
+        Some(path) => Box::new(BufReader::new(File::open(path).map_err(|e| format!("Couldn't open {}: {}", path.display(), e))?)),
+        None => Box::new(BufReader::new(io::stdin())),
+    };
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(line) = line else { break };
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(rx)
+}
@@ -0,0 +1,204 @@
+/// Framework-owned screen layout - status bar, game viewport, message log, chat and input line
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use tokio::time::Duration;
+
+use crate::client::chat::ChatPane;
+use crate::core::input::InputMode;
+use crate::core::terminal::{TerminalContext, TerminalColor};
+
+/// Log lines kept on screen under the game
+const VISIBLE_LOG_LINES: usize = 4;
+
+/// Log lines the client remembers
+const LOG_CAPACITY: usize = 100;
+
+/// Where the client stands with the server - shown in the status bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionStatus {
+    Connected,
+    Reconnecting { attempt: u32, max: u32 },
+}
+
+/// What a log line is about - decides its color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogKind {
+    /// Responses and announcements from the game
+    Info,
+    /// Protocol errors and refused actions
+    Error,
+}
+
+#[derive(Debug, Clone)]
+struct LogLine {
+    kind: LogKind,
+    text: String,
+}
+
+/// Everything on screen that isn't the game itself
+pub struct Layout {
+    player_name: String,
+    /// Game (and room) shown in the status bar
+    title: String,
+    spectating: bool,
+    input_mode: InputMode,
+    /// Quit chords spelled out for the hints - e.g. "Ctrl-C/Esc"
+    quit_hint: String,
+    status: ConnectionStatus,
+    latency: Option<Duration>,
+    log: VecDeque<LogLine>,
+    /// Game input typed so far - sent to `parse_line` on Enter
+    pub input: String,
+    pub chat: ChatPane,
+    /// Long-lived so every draw is diffed against the previous one
+    screen: RefCell<TerminalContext>,
+}
+
+impl Layout {
+    pub fn new(player_name: String, title: String, spectating: bool) -> Self {
+        Self {
+            player_name,
+            title,
+            spectating,
+            input_mode: InputMode::Line,
+            quit_hint: "Ctrl-C".to_string(),
+            status: ConnectionStatus::Connected,
+            latency: None,
+            log: VecDeque::new(),
+            input: String::new(),
+            chat: ChatPane::new(),
+            screen: RefCell::new(TerminalContext::new()),
+        }
+    }
+
+    /// Tell the input line how keys are used and how to get out
+    pub fn with_controls(mut self, input_mode: InputMode, quit_hint: String) -> Self {
+        self.input_mode = input_mode;
+        self.quit_hint = quit_hint;
+        self
+    }
+
+    /// Show a different name in the status bar - returns true if it changed
+    pub fn set_player_name(&mut self, name: &str) -> bool {
+        if self.player_name == name {
+            return false;
+        }
+        self.player_name = name.to_string();
+        true
+    }
+
+    pub fn set_status(&mut self, status: ConnectionStatus) {
+        self.status = status;
+        if status != ConnectionStatus::Connected {
+            self.latency = None;
+        }
+    }
+
+    pub fn set_latency(&mut self, latency: Duration) {
+        self.latency = Some(latency);
+    }
+
+    /// Add a line to the message log - empty text is ignored
+    pub fn log(&mut self, kind: LogKind, text: impl Into<String>) {
+        let text = text.into();
+        if text.is_empty() {
+            return;
+        }
+        if self.log.len() == LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        self.log.push_back(LogLine { kind, text });
+    }
+
+    /// Redraw the whole screen - `render_game` fills the viewport between status bar and log
+    /// Only cells that changed since the last draw reach the terminal
+    pub fn draw<F: FnOnce(&mut TerminalContext)>(&self, render_game: F) {
+        let mut ctx = self.screen.borrow_mut();
+        ctx.begin_frame();
+        self.render_status(&mut ctx);
+        ctx.empty_line();
+
+        render_game(&mut ctx);
+
+        self.render_log(&mut ctx);
+        self.chat.render(&mut ctx);
+        self.render_input(&mut ctx);
+        ctx.end_frame();
+    }
+
+    /// Redraw only the status bar - latency updates shouldn't repaint the game
+    pub fn draw_status(&self) {
+        let mut ctx = self.screen.borrow_mut();
+        ctx.begin_patch();
+        ctx.move_to(0, 0);
+        ctx.clear_line();
+        self.render_status(&mut ctx);
+        self.render_input(&mut ctx);
+        ctx.end_frame();
+    }
+
+    /// Redraw only the input line - cheap enough for every keystroke
+    pub fn draw_input(&self) {
+        let mut ctx = self.screen.borrow_mut();
+        ctx.begin_patch();
+        self.render_input(&mut ctx);
+        ctx.end_frame();
+    }
+
+    fn render_status(&self, ctx: &mut TerminalContext) {
+        let (dot, status, color) = match self.status {
+            ConnectionStatus::Connected => ("🟢", "connected".to_string(), TerminalColor::Green),
+            ConnectionStatus::Reconnecting { attempt, max } => {
+                ("🟡", format!("reconnecting ({}/{})", attempt, max), TerminalColor::Yellow)
+            }
+        };
+        let latency = match self.latency {
+            Some(latency) => format!("{}ms", latency.as_millis()),
+            None => "--ms".to_string(),
+        };
+        let who = if self.spectating {
+            format!("👀 {}", self.player_name)
+        } else {
+            self.player_name.clone()
+        };
+
+        ctx.print_colored(&format!("{} {}", dot, status), color);
+        ctx.print_line(&format!(" │ {} │ {} │ {}", latency, who, self.title));
+    }
+
+    fn render_log(&self, ctx: &mut TerminalContext) {
+        if self.log.is_empty() {
+            return;
+        }
+        ctx.empty_line();
+        let skip = self.log.len().saturating_sub(VISIBLE_LOG_LINES);
+        for line in self.log.iter().skip(skip) {
+            match line.kind {
+                LogKind::Info => ctx.print_line(&format!("• {}", line.text)),
+                LogKind::Error => ctx.print_colored_line(&format!("❌ {}", line.text), TerminalColor::Red),
+            }
+        }
+    }
+
+    /// Input line pinned to the bottom row - the terminal cursor sits at its end
+    fn render_input(&self, ctx: &mut TerminalContext) {
+        ctx.move_to(0, ctx.height().saturating_sub(1));
+        ctx.clear_line();
+
+        match self.chat.draft() {
+            Some(draft) => ctx.print(&format!("💬 > {}", draft)),
+            None if self.spectating => {
+                ctx.print_colored(&format!("Watching - Tab to chat, {} to quit", self.quit_hint), TerminalColor::Default)
+            }
+            // Keys go straight to the game - there's no line to show
+            None if self.input_mode == InputMode::Keys => {
+                ctx.print_colored(&format!("⌨️  Keys go to the game - Tab to chat, {} to quit", self.quit_hint), TerminalColor::Default)
+            }
+            None => {
+                ctx.print_colored("> ", TerminalColor::Cyan);
+                ctx.print(&self.input);
+            }
+        }
+    }
+}
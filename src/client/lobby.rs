@@ -0,0 +1,67 @@
+/// Lobby browsing - pick an existing room or create a new one
+use dialoguer::{theme::ColorfulTheme, Select};
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use tokio_tungstenite::tungstenite::{self, Message};
+
+use crate::core::protocol::{ClientMessage, ServerMessage, UntypedServerMessage};
+use crate::core::room::RoomInfo;
+
+/// Interactive room picker - returns the room the player should join
+pub async fn choose_room<S, R>(
+    ws_sender: &mut S,
+    ws_receiver: &mut R,
+    games: Vec<String>,
+    rooms: Vec<RoomInfo>,
+) -> Result<RoomInfo, Box<dyn std::error::Error + Send + Sync>>
+where
+    S: Sink<Message, Error = tungstenite::Error> + Unpin,
+    R: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+{
+    let mut items: Vec<String> = rooms
+        .iter()
+        .map(|r| {
+            let bots = if r.bots > 0 { format!(", {} 🤖", r.bots) } else { String::new() };
+            format!("🚪 {} - {} ({}/{} players{})", r.id, r.game, r.players, r.max_players, bots)
+        })
+        .collect();
+    items.push("➕ Create a new room".to_string());
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("🏢 Pick a room")
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    if let Some(room) = rooms.get(selection) {
+        return Ok(room.clone());
+    }
+
+    if games.is_empty() {
+        return Err("Lobby has no games available".into());
+    }
+
+    let game = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("🎮 Select a game for the new room")
+        .items(&games)
+        .default(0)
+        .interact()?;
+
+    let create = ClientMessage::<()>::CreateRoom { game: games[game].clone() };
+    ws_sender.send(Message::Text(serde_json::to_string(&create)?)).await?;
+
+    // Wait for the lobby to confirm - anything else on the socket is ignored
+    while let Some(msg_result) = ws_receiver.next().await {
+        if let Message::Text(text) = msg_result? {
+            match serde_json::from_str::<UntypedServerMessage>(&text) {
+                Ok(ServerMessage::RoomCreated(room)) => {
+                    println!("🏠 Created room {}", room.id);
+                    return Ok(room);
+                }
+                Ok(ServerMessage::Error(err)) => return Err(err.message.into()),
+                _ => {}
+            }
+        }
+    }
+
+    Err("Lobby closed the connection".into())
+}
@@ -1 +1,6 @@
 pub mod websocket_client;
+pub mod lobby;
+pub mod chat;
+pub mod layout;
+pub mod replay;
+pub mod headless;
@@ -0,0 +1,180 @@
+/// Replay player - shows a recorded session with the game's own renderer
+use crossterm::event::{self, Event as TermEvent, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use tokio::time::{Duration, Instant};
+
+use crate::core::context::Player;
+use crate::core::game::WebSocketGame;
+use crate::core::recording::Recording;
+use crate::core::renderer::GameRenderer;
+use crate::core::terminal::{TerminalContext, TerminalColor};
+
+/// Quiet stretches longer than this get squeezed - nobody wants to watch a player think for a minute
+const MAX_GAP: Duration = Duration::from_secs(2);
+
+/// Slowest and fastest playback
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 64.0;
+
+/// How a recording is played back - built from the `lanterm replay` flags
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// 2.0 plays twice as fast
+    pub speed: f64,
+    /// Watch through this player's eyes - default is the spectator view
+    pub viewer: Option<String>,
+    /// Start paused on the opening state
+    pub paused: bool,
+    /// Re-run the inputs and check the states instead of playing
+    pub verify: bool,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            viewer: None,
+            paused: false,
+            verify: false,
+        }
+    }
+}
+
+/// Play a recording in the terminal - Space pauses, arrows step, +/- change speed
+pub fn play<G, R>(recording: &Recording, options: &ReplayOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+where
+    G: WebSocketGame,
+    R: GameRenderer<G::View>,
+{
+    // Decode every state up front so a bad line fails before the screen takes over
+    let mut states: Vec<G::State> = vec![serde_json::from_value(recording.header.state.clone())?];
+    for (index, entry) in recording.entries.iter().enumerate() {
+        let state = serde_json::from_value(entry.state.clone())
+            .map_err(|e| format!("Line {}: state doesn't fit this version of {}: {}", index + 2, G::NAME, e))?;
+        states.push(state);
+    }
+
+    let viewer = match &options.viewer {
+        Some(name) => Some(find_player(recording, name).ok_or_else(|| format!("Nobody called '{}' played in this recording", name))?),
+        None => None,
+    };
+    let renderer = R::new(viewer.as_ref().map(|p| p.name.clone()).unwrap_or_else(|| "Spectator".to_string()));
+
+    let mut screen = TerminalContext::new();
+    let mut index = 0;
+    let mut speed = options.speed.clamp(MIN_SPEED, MAX_SPEED);
+    let mut paused = options.paused;
+    let mut next_at = Instant::now() + gap(recording, index, speed);
+
+    terminal::enable_raw_mode()?;
+    let result = loop {
+        let view = match &viewer {
+            Some(player) => G::view(&states[index], player),
+            None => G::spectator_view(&states[index]),
+        };
+        draw(&mut screen, recording, index, speed, paused, |ctx| renderer.render(&view, ctx));
+
+        let last = index + 1 == states.len();
+        let timeout = if paused || last {
+            Duration::from_millis(250)
+        } else {
+            next_at.saturating_duration_since(Instant::now())
+        };
+
+        match event::poll(timeout) {
+            Ok(true) => {}
+            Ok(false) => {
+                if !paused && !last {
+                    index += 1;
+                    next_at = Instant::now() + gap(recording, index, speed);
+                }
+                continue;
+            }
+            Err(e) => break Err(e.into()),
+        }
+
+        let key = match event::read() {
+            Ok(TermEvent::Key(key)) if key.kind != KeyEventKind::Release => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e.into()),
+        };
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break Ok(()),
+            KeyCode::Char('q') | KeyCode::Esc => break Ok(()),
+            KeyCode::Char(' ') => paused = !paused,
+            // Stepping pauses so the step sticks
+            KeyCode::Right | KeyCode::Char('n') | KeyCode::Char('.') => {
+                paused = true;
+                index = (index + 1).min(states.len() - 1);
+            }
+            KeyCode::Left | KeyCode::Char('p') | KeyCode::Char(',') => {
+                paused = true;
+                index = index.saturating_sub(1);
+            }
+            KeyCode::Home | KeyCode::Char('r') => index = 0,
+            KeyCode::End => index = states.len() - 1,
+            KeyCode::Char('+') | KeyCode::Char('=') => speed = (speed * 2.0).min(MAX_SPEED),
+            KeyCode::Char('-') => speed = (speed / 2.0).max(MIN_SPEED),
+            _ => continue,
+        }
+        next_at = Instant::now() + gap(recording, index, speed);
+    };
+    terminal::disable_raw_mode()?;
+
+    result
+}
+
+/// First time `name` shows up in the recording - that's who `--as` watches as
+fn find_player(recording: &Recording, name: &str) -> Option<Player> {
+    recording.entries.iter()
+        .filter_map(|entry| entry.event.player())
+        .find(|player| player.name == name && player.is_seated())
+        .cloned()
+}
+
+/// Wait before showing the state after `index` - the real gap, sped up and squeezed
+fn gap(recording: &Recording, index: usize, speed: f64) -> Duration {
+    let at = |i: usize| if i == 0 { 0 } else { recording.entries.get(i - 1).map(|e| e.at_ms).unwrap_or(0) };
+    let real = Duration::from_millis(at(index + 1).saturating_sub(at(index)));
+    real.min(MAX_GAP).div_f64(speed)
+}
+
+fn draw<F: FnOnce(&mut TerminalContext)>(ctx: &mut TerminalContext, recording: &Recording, index: usize, speed: f64, paused: bool, render_game: F) {
+    let header = &recording.header;
+    let at = Duration::from_millis(index.checked_sub(1).map(|i| recording.entries[i].at_ms).unwrap_or(0));
+    let (mode, color) = if paused {
+        ("⏸ paused", TerminalColor::Yellow)
+    } else if index == recording.entries.len() {
+        ("⏹ finished", TerminalColor::Cyan)
+    } else {
+        ("▶ playing", TerminalColor::Green)
+    };
+
+    ctx.begin_frame();
+    ctx.print_colored(mode, color);
+    ctx.print_line(&format!(
+        " │ 📼 {} @ {} │ {}/{} │ {} / {} │ {}x",
+        header.game, header.room, index, recording.entries.len(), clock(at), clock(recording.duration()), speed
+    ));
+    ctx.empty_line();
+
+    render_game(ctx);
+
+    let what = match index.checked_sub(1) {
+        Some(i) => recording.entries[i].event.describe(),
+        None => format!("Game started (seed {})", header.seed),
+    };
+    let bottom = ctx.height().saturating_sub(1);
+    ctx.move_to(0, bottom.saturating_sub(1));
+    ctx.clear_line();
+    ctx.print_line(&format!("• {}", what));
+    ctx.clear_line();
+    ctx.print_colored("Space pause • ←/→ step • +/- speed • Home restart • Q quit", TerminalColor::Default);
+    ctx.end_frame();
+}
+
+/// mm:ss
+fn clock(at: Duration) -> String {
+    let secs = at.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
@@ -1,53 +1,175 @@
 /// WebSocket-based game client - event-driven and clean!
-use std::io::{self, Write};
 use crossterm::{
-    event::{self, KeyCode, KeyEvent},
+    event::{self, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
     terminal::{self},
 };
-use tokio::time::Duration;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio::time::{Duration, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Serialize, Deserialize};
 
-use crate::core::websocket::GameMessage;
+use crate::client::headless::HeadlessClient;
+use crate::client::layout::{ConnectionStatus, Layout, LogKind};
+use crate::core::input::{InputMode, KeyChord, DEFAULT_QUIT_KEYS};
+use crate::core::protocol::{self, Capability, ClientMessage, GameMetadata, Hello, Role, ServerInfo, ServerMessage};
+use crate::core::terminal::TerminalContext;
+
+/// Give up after this many reconnect attempts in a row
+const MAX_RECONNECT_ATTEMPTS: u32 = 8;
+
+/// Backoff before the first retry - doubles every attempt
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Backoff never grows beyond this
+const MAX_BACKOFF: Duration = Duration::from_secs(8);
+
+/// How often the client pings the server to measure latency
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+
+pub(crate) type WsSender = futures_util::stream::SplitSink<tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>, Message>;
+pub(crate) type WsReceiver = futures_util::stream::SplitStream<tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>>;
+
+/// Why the game loop stopped
+enum LoopExit {
+    /// Player asked to quit
+    Quit,
+    /// Connection dropped - worth reconnecting
+    Disconnected,
+}
+
+/// How a client joins - passed through the registry like `HostOptions` on the host side
+#[derive(Debug, Clone, Default)]
+pub struct JoinOptions {
+    /// Lobby room to enter before joining - filled in by the room picker
+    pub room: Option<String>,
+    /// Play or just watch
+    pub role: Role,
+    /// Override the game's quit chords - empty keeps `WebSocketGame::QUIT_KEYS`
+    pub quit_keys: Vec<KeyChord>,
+}
 
 /// WebSocket game client
 pub struct WebSocketGameClient {
     player_name: String,
+    /// Game this client plays - declared in the handshake so mismatches fail up front
+    game: Option<GameMetadata>,
+    room: Option<String>,
+    role: Role,
+    /// Handed out by the server on join - re-presented to reclaim the seat after a drop
+    resume_token: Option<String>,
+    input_mode: InputMode,
+    quit_keys: Vec<KeyChord>,
 }
 
 impl WebSocketGameClient {
     pub fn new(name: String) -> Self {
         Self {
             player_name: name,
+            game: None,
+            room: None,
+            role: Role::Player,
+            resume_token: None,
+            input_mode: InputMode::Line,
+            quit_keys: DEFAULT_QUIT_KEYS.to_vec(),
         }
     }
 
+    /// Declare the game this client was built for - the server rejects incompatible versions
+    pub fn with_game(mut self, game: GameMetadata) -> Self {
+        self.game = Some(game);
+        self
+    }
+
+    /// Watch instead of playing - no seat is taken and input isn't sent
+    pub fn spectate(mut self) -> Self {
+        self.role = Role::Spectator;
+        self
+    }
+
+    /// Enter this lobby room before joining - needed when talking to a `LobbyServer`
+    pub fn with_room(mut self, room_id: String) -> Self {
+        self.room = Some(room_id);
+        self
+    }
+
+    /// Pick how keys reach the game - lines, single keypresses or both
+    pub fn with_input_mode(mut self, mode: InputMode) -> Self {
+        self.input_mode = mode;
+        self
+    }
+
+    /// Chords that leave the game - an empty list keeps the current ones
+    pub fn with_quit_keys(mut self, keys: Vec<KeyChord>) -> Self {
+        if !keys.is_empty() {
+            self.quit_keys = keys;
+        }
+        self
+    }
+
     /// Connect and run the event-driven game loop
-    pub async fn connect_and_play<State, Input, F, I>(
+    /// `render_fn` draws the game into the viewport - the framework owns the rest of the screen
+    /// `input_fn` parses typed lines and `key_fn` maps single keys, depending on the input mode
+    pub async fn connect_and_play<State, Input, F, I, K>(
         &mut self,
         url: &str,
         mut render_fn: F,
         mut input_fn: I,
+        mut key_fn: K,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
     where
         State: for<'de> Deserialize<'de> + Serialize + Send + Sync + std::fmt::Debug + 'static,
         Input: Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static,
-        F: FnMut(&State),
+        F: FnMut(&State, &mut TerminalContext),
         I: FnMut(&str) -> Option<Input>,
+        K: FnMut(&KeyEvent) -> Option<Input>,
     {
         // Connect without printing - let game handle all output
-        let (ws_stream, _) = connect_async(url).await?;
-        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        // The first connection must succeed - later drops are retried with backoff
+        let (mut ws_sender, mut ws_receiver) = self.connect::<Input>(url).await?;
         
-        // Send join message
-        let join_json = format!("{{\"PlayerJoin\":{{\"name\":\"{}\"}}}}", self.player_name);
-        ws_sender.send(Message::Text(join_json)).await?;
+        let mut layout = Layout::new(self.player_name.clone(), self.title(), self.role == Role::Spectator)
+            .with_controls(self.input_mode, self.quit_hint());
         
         // Enable raw mode for input
         terminal::enable_raw_mode()?;
         
-        let result = self.run_game_loop(&mut ws_sender, &mut ws_receiver, &mut render_fn, &mut input_fn).await;
+        let mut last_state: Option<(String, State)> = None;
+        let mut attempt = 0;
+        let result = loop {
+            match self.run_game_loop(&mut ws_sender, &mut ws_receiver, &mut layout, &mut last_state, &mut render_fn, &mut input_fn, &mut key_fn).await {
+                Ok(LoopExit::Quit) => break Ok(()),
+                Err(e) => break Err(e),
+                Ok(LoopExit::Disconnected) => {}
+            }
+            
+            // Connection dropped - reconnect and present the resume token
+            let reconnected = loop {
+                attempt += 1;
+                if attempt > MAX_RECONNECT_ATTEMPTS {
+                    break None;
+                }
+                
+                let backoff = (INITIAL_BACKOFF * 2u32.pow(attempt - 1)).min(MAX_BACKOFF);
+                layout.set_status(ConnectionStatus::Reconnecting { attempt, max: MAX_RECONNECT_ATTEMPTS });
+                layout.draw_status();
+                tokio::time::sleep(backoff).await;
+                
+                if let Ok(streams) = self.connect::<Input>(url).await {
+                    break Some(streams);
+                }
+            };
+            
+            match reconnected {
+                Some((sender, receiver)) => {
+                    ws_sender = sender;
+                    ws_receiver = receiver;
+                    attempt = 0;
+                    layout.set_status(ConnectionStatus::Connected);
+                    layout.draw_status();
+                }
+                None => break Err("Lost connection to the server".into()),
+            }
+        };
         
         // Always disable raw mode
         terminal::disable_raw_mode()?;
@@ -55,102 +177,290 @@ impl WebSocketGameClient {
         result
     }
 
-    async fn run_game_loop<State, Input, F, I>(
+    /// Connect without a terminal - events come back typed and nothing is drawn
+    /// The handshake, lobby room and join are the same as `connect_and_play`, but a drop isn't retried
+    pub async fn connect_headless<View, Input>(&self, url: &str) -> Result<HeadlessClient<View, Input>, Box<dyn std::error::Error + Send + Sync>>
+    where
+        View: for<'de> Deserialize<'de> + Send + 'static,
+        Input: Serialize,
+    {
+        let (ws_sender, ws_receiver) = self.connect::<Input>(url).await?;
+        Ok(HeadlessClient::new(ws_sender, ws_receiver))
+    }
+
+    /// Quit chords for the input line hint - e.g. "Ctrl-C/Esc"
+    fn quit_hint(&self) -> String {
+        self.quit_keys.iter().map(|k| k.to_string()).collect::<Vec<_>>().join("/")
+    }
+
+    /// Game and room for the status bar
+    fn title(&self) -> String {
+        let game = self.game.as_ref().map(|g| g.name.as_str()).unwrap_or("lanterm");
+        match &self.room {
+            Some(room) => format!("{} @ {}", game, room),
+            None => game.to_string(),
+        }
+    }
+
+    /// Open the socket, shake hands, enter the lobby room if any, and (re)join the game
+    async fn connect<Input>(&self, url: &str) -> Result<(WsSender, WsReceiver), Box<dyn std::error::Error + Send + Sync>>
+    where
+        Input: Serialize,
+    {
+        let (ws_stream, _) = connect_async(url).await?;
+        if let MaybeTlsStream::Plain(tcp) = ws_stream.get_ref() {
+            let _ = tcp.set_nodelay(true);
+        }
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        
+        // Version check first - a mismatched server tells us why instead of sending garbage
+        let welcome = protocol::client_handshake(&mut ws_sender, &mut ws_receiver, Hello::new(self.game.as_ref())).await?;
+        
+        match (&welcome.server, &self.room) {
+            (ServerInfo::Lobby { .. }, Some(room_id)) => {
+                // Pick the lobby room first - the lobby forwards everything after this to it
+                let enter = ClientMessage::<Input>::JoinRoom { room_id: room_id.clone() };
+                ws_sender.send(Message::Text(serde_json::to_string(&enter)?)).await?;
+                Self::wait_for_room(&mut ws_receiver).await?;
+            }
+            (ServerInfo::Lobby { .. }, None) => {
+                return Err("Server is a lobby - pick a room to join first".into());
+            }
+            (ServerInfo::Game(_), _) => {}
+        }
+        
+        let join = match self.role {
+            // With a resume token the server hands our old seat back
+            Role::Player => ClientMessage::<Input>::PlayerJoin {
+                name: self.player_name.clone(),
+                resume_token: if welcome.capabilities.contains(&Capability::Resume) {
+                    self.resume_token.clone()
+                } else {
+                    None
+                },
+            },
+            Role::Spectator => {
+                if !welcome.capabilities.contains(&Capability::Spectate) {
+                    return Err("Server doesn't support spectators".into());
+                }
+                ClientMessage::<Input>::Spectate { name: self.player_name.clone() }
+            }
+        };
+        ws_sender.send(Message::Text(serde_json::to_string(&join)?)).await?;
+        
+        Ok((ws_sender, ws_receiver))
+    }
+
+    /// Wait for the lobby to confirm we're inside the room - or tell us why not
+    async fn wait_for_room(ws_receiver: &mut WsReceiver) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        while let Some(msg_result) = ws_receiver.next().await {
+            if let Message::Text(text) = msg_result? {
+                match serde_json::from_str::<protocol::UntypedServerMessage>(&text) {
+                    Ok(ServerMessage::RoomEntered { .. }) => return Ok(()),
+                    Ok(ServerMessage::Error(err)) => return Err(err.message.into()),
+                    _ => {}
+                }
+            }
+        }
+        Err("Lobby closed the connection".into())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn run_game_loop<State, Input, F, I, K>(
         &mut self,
-        ws_sender: &mut futures_util::stream::SplitSink<tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>, Message>,
-        ws_receiver: &mut futures_util::stream::SplitStream<tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>>,
+        ws_sender: &mut WsSender,
+        ws_receiver: &mut WsReceiver,
+        layout: &mut Layout,
+        last_state: &mut Option<(String, State)>,
         render_fn: &mut F,
         input_fn: &mut I,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+        key_fn: &mut K,
+    ) -> Result<LoopExit, Box<dyn std::error::Error + Send + Sync>>
     where
-        State: for<'de> Deserialize<'de> + Send + Sync + std::fmt::Debug + 'static,
+        State: for<'de> Deserialize<'de> + Serialize + Send + Sync + std::fmt::Debug + 'static,
         Input: Serialize + for<'de> Deserialize<'de> + Send + Sync + 'static,
-        F: FnMut(&State),
+        F: FnMut(&State, &mut TerminalContext),
         I: FnMut(&str) -> Option<Input>,
+        K: FnMut(&KeyEvent) -> Option<Input>,
     {
-        let mut last_state: Option<String> = None;
-        let mut input_line = String::new();
+        let mut ping = tokio::time::interval(PING_INTERVAL);
+        let mut ping_sent: Option<Instant> = None;
+
+        // Whatever was on screen before a reconnect comes back right away
+        Self::redraw(layout, last_state, render_fn);
 
         loop {
             tokio::select! {
-                // Handle keyboard input - line-based for coordinates!
+                // Handle keyboard input - lines, single keys or both depending on the game
                 _ = tokio::time::sleep(Duration::from_millis(50)) => {
                     if event::poll(Duration::from_millis(1))? {
-                        if let event::Event::Key(KeyEvent { code, .. }) = event::read()? {
-                            match code {
-                                KeyCode::Char('q') => {
-                                    // Just close the connection, server will handle cleanup
-                                    break;
+                        if let event::Event::Key(key) = event::read()? {
+                            // Some terminals report releases too - only presses and repeats count
+                            if key.kind == KeyEventKind::Release {
+                                continue;
+                            }
+                            let code = key.code;
+                            let typing = layout.chat.is_typing();
+
+                            // While chatting only chords with a modifier quit - Esc and plain keys belong to the draft
+                            if self.quit_keys.iter().any(|chord| chord.matches(&key) && (!typing || !chord.modifiers.is_empty())) {
+                                // Leave explicitly so the server frees our seat instead of holding it
+                                let leave = ClientMessage::<Input>::PlayerLeave;
+                                if let Ok(json) = serde_json::to_string(&leave) {
+                                    let _ = ws_sender.send(Message::Text(json)).await;
+                                }
+                                return Ok(LoopExit::Quit);
+                            }
+
+                            // Ctrl and Alt chords never end up as typed text
+                            let typed = match code {
+                                KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => Some(c),
+                                _ => None,
+                            };
+
+                            if typing {
+                                // Chat mode - keys never reach the game's parse_line
+                                match code {
+                                    KeyCode::Enter => {
+                                        if let Some(text) = layout.chat.take() {
+                                            if let Ok(json) = serde_json::to_string(&ClientMessage::<Input>::Chat(text)) {
+                                                let _ = ws_sender.send(Message::Text(json)).await;
+                                            }
+                                        }
+                                    }
+                                    KeyCode::Esc => layout.chat.cancel(),
+                                    KeyCode::Backspace => layout.chat.backspace(),
+                                    _ => {
+                                        if let Some(c) = typed {
+                                            layout.chat.type_char(c);
+                                        }
+                                    }
                                 }
+                                layout.draw_input();
+                                continue;
+                            }
+
+                            if code == KeyCode::Tab {
+                                layout.chat.start();
+                                layout.draw_input();
+                                continue;
+                            }
+
+                            // Keys the game maps go out straight away - no Enter needed
+                            if self.input_mode.uses_keys() && self.role == Role::Player {
+                                if let Some(input) = key_fn(&key) {
+                                    if let Ok(json) = serde_json::to_string(&ClientMessage::PlayerInput(input)) {
+                                        let _ = ws_sender.send(Message::Text(json)).await;
+                                    }
+                                    continue;
+                                }
+                            }
+                            if !self.input_mode.uses_line() {
+                                continue;
+                            }
+
+                            match code {
                                 KeyCode::Enter => {
-                                    // Process the complete line
-                                    if let Some(input) = input_fn(&input_line) {
-                                        if let Ok(input_json) = serde_json::to_string(&input) {
-                                            let msg = format!("{{\"PlayerInput\":{}}}", input_json);
-                                            let _ = ws_sender.send(Message::Text(msg)).await;
+                                    // Process the complete line - spectators only watch
+                                    if let Some(input) = input_fn(&layout.input).filter(|_| self.role == Role::Player) {
+                                        if let Ok(json) = serde_json::to_string(&ClientMessage::PlayerInput(input)) {
+                                            let _ = ws_sender.send(Message::Text(json)).await;
                                         }
+                                    } else if !layout.input.trim().is_empty() && self.role == Role::Player {
+                                        layout.log(LogKind::Error, format!("Couldn't understand '{}'", layout.input.trim()));
+                                        layout.input.clear();
+                                        Self::redraw(layout, last_state, render_fn);
+                                        continue;
                                     }
-                                    input_line.clear();
+                                    layout.input.clear();
                                 }
                                 KeyCode::Backspace => {
-                                    if !input_line.is_empty() {
-                                        input_line.pop();
-                                    }
+                                    layout.input.pop();
                                 }
-                                KeyCode::Char(c) => {
-                                    input_line.push(c);
+                                _ => {
+                                    if let Some(c) = typed {
+                                        layout.input.push(c);
+                                    }
                                 }
-                                _ => {}
                             }
+                            layout.draw_input();
                         }
                     }
                 }
                 
+                // Measure latency with WebSocket pings - the server answers them automatically
+                _ = ping.tick() => {
+                    let _ = ws_sender.send(Message::Ping(Vec::new())).await;
+                    ping_sent = Some(Instant::now());
+                }
+                
                 // Handle WebSocket messages - pure events!
                 msg_result = ws_receiver.next() => {
                     match msg_result {
                         Some(Ok(Message::Text(text))) => {
-                            if let Ok(game_msg) = serde_json::from_str::<GameMessage<State, Input>>(&text) {
+                            if let Ok(game_msg) = serde_json::from_str::<ServerMessage<State>>(&text) {
                                 match game_msg {
-                                    GameMessage::StateUpdate(state) => {
+                                    ServerMessage::Joined { resume_token, name, .. } => {
+                                        self.resume_token = Some(resume_token);
+                                        // Someone else had our name - the room picked a new one
+                                        if !name.is_empty() && name != self.player_name && layout.set_player_name(&name) {
+                                            layout.log(LogKind::Info, format!("Name taken - you're playing as '{}'", name));
+                                            Self::redraw(layout, last_state, render_fn);
+                                        }
+                                    }
+                                    ServerMessage::StateUpdate(state) => {
                                         // Only render if state changed
                                         let state_str = format!("{:?}", state);
-                                        if last_state.as_ref() != Some(&state_str) {
-                                            render_fn(&state);
-                                            last_state = Some(state_str);
+                                        if last_state.as_ref().map(|(s, _)| s) != Some(&state_str) {
+                                            *last_state = Some((state_str, state));
+                                            Self::redraw(layout, last_state, render_fn);
                                         }
                                     }
-                                    GameMessage::Message(msg) => {
-                                        // Don't print messages here - they interfere with game rendering
-                                        // The game renderer handles all output
+                                    ServerMessage::Message(msg) => {
+                                        layout.log(LogKind::Info, msg);
+                                        Self::redraw(layout, last_state, render_fn);
                                     }
-                                    GameMessage::Error(err) => {
-                                        // Only print critical errors that need immediate attention
-                                        if err.contains("disconnect") || err.contains("connection") {
-                                            eprintln!("❌ {}", err);
-                                        }
+                                    ServerMessage::Error(err) => {
+                                        layout.log(LogKind::Error, err.message);
+                                        Self::redraw(layout, last_state, render_fn);
+                                    }
+                                    ServerMessage::Chat(message) => {
+                                        layout.chat.push(message);
+                                        Self::redraw(layout, last_state, render_fn);
+                                    }
+                                    ServerMessage::ChatHistory(history) => {
+                                        layout.chat.replace(history);
+                                        Self::redraw(layout, last_state, render_fn);
                                     }
                                     _ => {}
                                 }
                             }
                         }
-                        Some(Ok(Message::Close(_))) => {
-                            break;
+                        Some(Ok(Message::Pong(_))) => {
+                            if let Some(sent) = ping_sent.take() {
+                                layout.set_latency(sent.elapsed());
+                                layout.draw_status();
+                            }
                         }
-                        Some(Err(e)) => {
-                            // Only log to stderr without newlines to avoid terminal interference
-                            eprintln!("WebSocket error: {}", e);
-                            break;
+                        Some(Ok(Message::Close(_))) | None => {
+                            return Ok(LoopExit::Disconnected);
                         }
-                        None => {
-                            break;
+                        Some(Err(_)) => {
+                            // The status bar shows the drop - printing here would tear the layout
+                            return Ok(LoopExit::Disconnected);
                         }
                         _ => {}
                     }
                 }
             }
         }
-        
-        Ok(())
     }
-}
\ No newline at end of file
+
+    /// Draw the whole layout with the last view in the game viewport
+    fn redraw<State, F: FnMut(&State, &mut TerminalContext)>(layout: &Layout, last_state: &Option<(String, State)>, render_fn: &mut F) {
+        layout.draw(|ctx| match last_state {
+            Some((_, state)) => render_fn(state, ctx),
+            None => ctx.print_line("⏳ Waiting for the game..."),
+        });
+    }
+}
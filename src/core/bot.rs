@@ -0,0 +1,16 @@
+/// Computer players - fill empty seats so a game can be tried alone
+use crate::core::context::{GameRng, Player};
+use crate::core::game::WebSocketGame;
+
+/// A computer player - sees exactly the view a human in its seat would get, and answers with input
+/// The room asks again whenever the view changes, after a short think, so one move per call is plenty
+pub trait Bot<G: WebSocketGame>: Send + Sync {
+    /// Name the bot's seat gets - made unique like anyone else's
+    fn name(&self) -> &str {
+        "Bot"
+    }
+
+    /// Next move for what this seat sees right now - `None` waits (not our turn, game over)
+    /// `rng` belongs to the bot, so its choices never shift the game's own rolls
+    fn choose(&mut self, view: &G::View, me: &Player, rng: &mut GameRng) -> Option<G::Input>;
+}
@@ -0,0 +1,71 @@
+/// Room chat - history for late joiners and a per-session rate limit
+use std::collections::VecDeque;
+
+use tokio::time::{Duration, Instant};
+
+use crate::core::protocol::ChatMessage;
+
+/// Messages a room remembers for people who join later
+pub const CHAT_HISTORY: usize = 50;
+
+/// Longer messages are cut down to this many characters
+pub const CHAT_MAX_LEN: usize = 280;
+
+/// A session may send this many messages per `CHAT_WINDOW`
+pub const CHAT_BURST: usize = 5;
+
+/// Sliding window for the rate limit
+pub const CHAT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Bounded chat history - oldest messages fall off first
+#[derive(Debug, Default)]
+pub struct ChatLog {
+    messages: VecDeque<ChatMessage>,
+}
+
+impl ChatLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, message: ChatMessage) {
+        if self.messages.len() == CHAT_HISTORY {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+    }
+
+    /// Everything remembered, oldest first
+    pub fn history(&self) -> Vec<ChatMessage> {
+        self.messages.iter().cloned().collect()
+    }
+}
+
+/// Sliding-window limiter - one per session so a spammer only mutes themselves
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    sent: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    /// Record a message if the window has room for it
+    pub fn allow(&mut self, now: Instant) -> bool {
+        while self.sent.front().is_some_and(|&t| now.duration_since(t) >= CHAT_WINDOW) {
+            self.sent.pop_front();
+        }
+        if self.sent.len() >= CHAT_BURST {
+            return false;
+        }
+        self.sent.push_back(now);
+        true
+    }
+}
+
+/// Trim whitespace and cap the length - `None` if nothing is left to say
+pub fn clean(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    Some(text.chars().take(CHAT_MAX_LEN).collect())
+}
@@ -0,0 +1,200 @@
+/// Game context - what the framework hands every hook besides the state
+use std::collections::BTreeMap;
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+
+use crate::core::timers::Timers;
+
+/// Seedable randomness owned by the room - the same seed plays out the same game
+pub type GameRng = StdRng;
+
+/// RNG that replays exactly for a given seed
+pub fn seeded_rng(seed: u64) -> GameRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// Game options by name - the game's `SETTINGS` with whatever the host picked, see `core::settings`
+pub type Settings = BTreeMap<String, String>;
+
+/// Seed for hosts that didn't pick one - logged so the run can still be reproduced
+pub fn random_seed() -> u64 {
+    rand::random()
+}
+
+/// Stable id the room gives a session - survives reconnects, unlike names nobody checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub struct PlayerId(pub u64);
+
+impl PlayerId {
+    /// Id of nobody in particular - real sessions count up from 1
+    pub const NOBODY: PlayerId = PlayerId(0);
+}
+
+impl fmt::Display for PlayerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// What someone is in the room - the host is the seated player who has been there longest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerRole {
+    Host,
+    Player,
+    Spectator,
+}
+
+/// Someone in the room as the game sees them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Player {
+    pub id: PlayerId,
+    /// Display name - unique within the room, but use `id` to tell players apart
+    pub name: String,
+    /// Seat index among players in join order - `None` for spectators
+    pub seat: Option<usize>,
+    pub role: PlayerRole,
+}
+
+impl Player {
+    /// A viewer with no seat and no name - what default spectator views are built for
+    pub fn nobody() -> Self {
+        Self { id: PlayerId::NOBODY, name: String::new(), seat: None, role: PlayerRole::Spectator }
+    }
+
+    pub fn is_seated(&self) -> bool {
+        self.seat.is_some()
+    }
+
+    /// Id and name - what games usually keep in their state
+    pub fn tag(&self) -> PlayerTag {
+        PlayerTag { id: self.id, name: self.name.clone() }
+    }
+}
+
+/// A player as stored in game state - stable id plus the name to show
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerTag {
+    pub id: PlayerId,
+    pub name: String,
+}
+
+/// Who a game message goes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recipient {
+    /// One session, player or spectator
+    Player(PlayerId),
+    /// Everyone with a seat
+    Players,
+    /// Everyone watching
+    Spectators,
+    Everyone,
+}
+
+/// A message a hook queued - the room delivers it once the hook returns
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outgoing {
+    pub to: Recipient,
+    pub text: String,
+}
+
+/// Passed to every game hook - who acted, the room's RNG and clock, timers and an outbox
+pub struct GameContext<'a> {
+    /// The room's RNG - use it instead of `thread_rng` so `--seed` replays the game
+    pub rng: &'a mut GameRng,
+    /// Timers to arm or cancel - the room applies them once the hook returns
+    pub timers: Timers,
+    player: Option<Player>,
+    now: Duration,
+    outbox: Vec<Outgoing>,
+    settings: Settings,
+}
+
+impl<'a> GameContext<'a> {
+    /// Context for a hook nobody in particular triggered - `new_game`, ticks and timers
+    pub fn new(rng: &'a mut GameRng, now: Duration) -> Self {
+        Self { rng, timers: Timers::new(), player: None, now, outbox: Vec::new(), settings: Settings::new() }
+    }
+
+    /// Hand the host's settings to `new_game` - other hooks keep whatever they need in the state
+    pub fn with_settings(mut self, settings: Settings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Context for a hook one player triggered - joins, leaves and input
+    pub fn for_player(rng: &'a mut GameRng, now: Duration, player: Player) -> Self {
+        Self { player: Some(player), ..Self::new(rng, now) }
+    }
+
+    /// Who triggered this hook - `None` for `new_game`, ticks and timers
+    pub fn player(&self) -> Option<&Player> {
+        self.player.as_ref()
+    }
+
+    /// Id of whoever triggered this hook - `PlayerId::NOBODY` if nobody did
+    pub fn player_id(&self) -> PlayerId {
+        self.player.as_ref().map(|p| p.id).unwrap_or(PlayerId::NOBODY)
+    }
+
+    /// Name of whoever triggered this hook - empty if nobody did
+    pub fn player_name(&self) -> &str {
+        self.player.as_ref().map(|p| p.name.as_str()).unwrap_or("")
+    }
+
+    /// What `key` is set to for this room - `None` if the game has no such setting
+    pub fn setting(&self, key: &str) -> Option<&str> {
+        self.settings.get(key).map(String::as_str)
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Time since the room opened - the simulation's virtual clock under `lanterm::testing`
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// Message only the player who triggered this hook sees
+    pub fn reply(&mut self, text: impl Into<String>) {
+        if let Some(id) = self.player.as_ref().map(|p| p.id) {
+            self.send_to(id, text);
+        }
+    }
+
+    /// Message one player - or one spectator
+    pub fn send_to(&mut self, id: PlayerId, text: impl Into<String>) {
+        self.push(Recipient::Player(id), text);
+    }
+
+    /// Message everyone in the room, spectators included
+    pub fn broadcast(&mut self, text: impl Into<String>) {
+        self.push(Recipient::Everyone, text);
+    }
+
+    /// Message everyone with a seat
+    pub fn to_players(&mut self, text: impl Into<String>) {
+        self.push(Recipient::Players, text);
+    }
+
+    /// Message only the people watching - commentary players shouldn't see
+    pub fn to_spectators(&mut self, text: impl Into<String>) {
+        self.push(Recipient::Spectators, text);
+    }
+
+    /// Timers and messages the hook produced
+    pub fn into_parts(self) -> (Timers, Vec<Outgoing>) {
+        (self.timers, self.outbox)
+    }
+
+    fn push(&mut self, to: Recipient, text: impl Into<String>) {
+        let text = text.into();
+        if !text.is_empty() {
+            self.outbox.push(Outgoing { to, text });
+        }
+    }
+}
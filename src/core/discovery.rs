@@ -0,0 +1,194 @@
+/// LAN discovery - servers shout on UDP, `lanterm discover` listens
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use serde::{Serialize, Deserialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::UdpSocket;
+use tokio::time::{Duration, Instant};
+
+/// UDP port beacons are sent to and discoverers listen on
+pub const DISCOVERY_PORT: u16 = 4747;
+
+/// How often a hosting server announces itself
+pub const BEACON_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Largest UDP payload - the receive buffer never truncates a beacon
+const MAX_DATAGRAM: usize = 65_507;
+
+/// What a server announces about itself - mirrors `websocket::GameMetadata`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Announcement {
+    /// Unique per server process - lets discoverers merge duplicate beacons
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub players: usize,
+    #[serde(default)]
+    pub spectators: usize,
+    /// WebSocket port - the address comes from the beacon's source IP
+    pub port: u16,
+}
+
+/// A server found on the LAN
+#[derive(Debug, Clone)]
+pub struct DiscoveredServer {
+    pub addr: SocketAddr,
+    pub announcement: Announcement,
+}
+
+/// Announce a server forever - `announcement` is rebuilt every tick so player counts stay fresh
+/// Beacons go to the broadcast address and to loopback, so same-machine discovery always works.
+/// Servers bound to loopback (`bind_ip`) only announce there - nobody else could connect anyway.
+pub async fn run_beacon<F, Fut>(bind_ip: IpAddr, announcement: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Announcement>,
+{
+    beacon_on(DISCOVERY_PORT, bind_ip, announcement).await
+}
+
+async fn beacon_on<F, Fut>(port: u16, bind_ip: IpAddr, announcement: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Announcement>,
+{
+    let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await {
+        Ok(socket) => socket,
+        Err(e) => {
+            eprintln!("📡 LAN beacon disabled: {}", e);
+            return;
+        }
+    };
+    let _ = socket.set_broadcast(true);
+
+    let mut targets = vec![SocketAddr::from((Ipv4Addr::LOCALHOST, port))];
+    if !bind_ip.is_loopback() {
+        targets.push(SocketAddr::from((Ipv4Addr::BROADCAST, port)));
+    }
+    let mut warned = false;
+    let mut interval = tokio::time::interval(BEACON_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let Ok(payload) = serde_json::to_vec(&announcement().await) else {
+            continue;
+        };
+
+        for &target in &targets {
+            // Hosts without a LAN route can't broadcast - warn once and keep going
+            if let Err(e) = socket.send_to(&payload, target).await {
+                if !warned {
+                    eprintln!("📡 LAN beacon to {} failed: {}", target, e);
+                    warned = true;
+                }
+            }
+        }
+    }
+}
+
+/// Listen for beacons for `timeout` and return every server heard from
+pub async fn discover(timeout: Duration) -> Result<Vec<DiscoveredServer>, Box<dyn std::error::Error + Send + Sync>> {
+    listen_on(DISCOVERY_PORT, timeout).await
+}
+
+async fn listen_on(port: u16, timeout: Duration) -> Result<Vec<DiscoveredServer>, Box<dyn std::error::Error + Send + Sync>> {
+    let socket = bind_shared(port)?;
+    let deadline = Instant::now() + timeout;
+    let mut found: HashMap<String, DiscoveredServer> = HashMap::new();
+    let mut buf = vec![0u8; MAX_DATAGRAM];
+
+    while let Ok(received) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buf)).await {
+        let (len, source) = received?;
+        let Ok(announcement) = serde_json::from_slice::<Announcement>(&buf[..len]) else {
+            continue;
+        };
+
+        let server = DiscoveredServer {
+            addr: SocketAddr::new(source.ip(), announcement.port),
+            announcement,
+        };
+
+        // The same server may arrive via broadcast and loopback - prefer the LAN address
+        match found.get(&server.announcement.id) {
+            Some(existing) if !existing.addr.ip().is_loopback() => {}
+            _ => {
+                found.insert(server.announcement.id.clone(), server);
+            }
+        }
+    }
+
+    let mut servers: Vec<DiscoveredServer> = found.into_values().collect();
+    servers.sort_by(|a, b| a.announcement.name.cmp(&b.announcement.name).then(a.addr.cmp(&b.addr)));
+    Ok(servers)
+}
+
+/// Bind the discovery port with SO_REUSEADDR so a second discoverer doesn't fail to start.
+/// Broadcast beacons reach every bound socket, but the kernel hands each loopback
+/// beacon to just one of them - concurrent discoverers may each miss same-machine servers.
+fn bind_shared(port: u16) -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_broadcast(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)).into())?;
+    UdpSocket::from_std(socket.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announcement(description: String) -> Announcement {
+        Announcement {
+            id: "server-1".to_string(),
+            name: "Test".to_string(),
+            version: "1.0.0".to_string(),
+            description,
+            players: 2,
+            spectators: 1,
+            port: 9001,
+        }
+    }
+
+    /// A free UDP port, so the test never collides with a real `lanterm host`
+    fn free_port() -> u16 {
+        std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap().local_addr().unwrap().port()
+    }
+
+    #[tokio::test]
+    async fn beacon_is_discovered_on_loopback() {
+        let port = free_port();
+        let beacon = tokio::spawn(beacon_on(port, IpAddr::V4(Ipv4Addr::LOCALHOST), || async {
+            announcement("Hangman".to_string())
+        }));
+
+        let servers = listen_on(port, BEACON_INTERVAL * 2).await.unwrap();
+        beacon.abort();
+
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].addr, SocketAddr::from((Ipv4Addr::LOCALHOST, 9001)));
+        assert_eq!(servers[0].announcement.players, 2);
+        assert_eq!(servers[0].announcement.spectators, 1);
+    }
+
+    #[tokio::test]
+    async fn large_beacon_is_not_truncated() {
+        let port = free_port();
+        let description = "x".repeat(8 * 1024);
+        let expected = description.clone();
+        let beacon = tokio::spawn(beacon_on(port, IpAddr::V4(Ipv4Addr::LOCALHOST), move || {
+            let description = description.clone();
+            async move { announcement(description) }
+        }));
+
+        let servers = listen_on(port, BEACON_INTERVAL * 2).await.unwrap();
+        beacon.abort();
+
+        assert_eq!(servers.len(), 1);
+        assert_eq!(servers[0].announcement.description, expected);
+    }
+}
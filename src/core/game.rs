@@ -1,34 +1,103 @@
 /// Core game interface for the lanterm framework
 use serde::Serialize;
 
+use crate::core::input::{InputMode, KeyChord, KeyEvent, DEFAULT_QUIT_KEYS};
+use crate::core::bot::Bot;
+use crate::core::context::{GameContext, Player, Settings};
+use crate::core::settings::SettingSpec;
+
 /// Main game trait that all games must implement
 /// This provides a standard interface for WebSocket-based multiplayer games
 pub trait WebSocketGame: Clone + Send + Sync + 'static {
-    /// Game state that gets serialized and sent to clients
+    /// Authoritative game state - lives on the server and is never sent as-is
     type State: Clone + Send + Sync + Serialize + for<'de> serde::Deserialize<'de> + std::fmt::Debug + 'static;
-    
+
+    /// Per-viewer projection of the state that gets serialized and sent to clients
+    type View: Clone + Send + Sync + Serialize + for<'de> serde::Deserialize<'de> + std::fmt::Debug + 'static;
+
     /// Input type that clients send to the server
     type Input: Clone + Send + Sync + Serialize + for<'de> serde::Deserialize<'de> + std::fmt::Debug + 'static;
-    
+
     // Metadata as associated constants - no factory needed!
     const NAME: &'static str;
     const DESCRIPTION: &'static str;
     const MIN_PLAYERS: usize;
     const MAX_PLAYERS: usize;
-    
+
+    /// Game version - clients on an incompatible major version get turned away at the handshake
+    /// Bump it whenever State, View or Input change shape
+    const VERSION: &'static str = "1.0.0";
+
+    /// Server ticks per second - 0 keeps the game purely input-driven
+    const TICK_RATE: u32 = 0;
+
+    /// Typed lines, single keypresses, or both - see `InputMode`
+    const INPUT_MODE: InputMode = InputMode::Line;
+
+    /// Chords that leave the game - players can still override them with `--quit-key`
+    const QUIT_KEYS: &'static [KeyChord] = DEFAULT_QUIT_KEYS;
+
+    /// Settings the host can pick with `--set key=value` or at the host prompts - none by default
+    const SETTINGS: &'static [SettingSpec] = &[];
+
     /// Create a new game instance with initial state
-    fn new_game() -> Self::State;
-    
+    /// Draw any randomness from `ctx.rng` - it's seeded per room so games can be replayed
+    /// `ctx.setting` has every key in `SETTINGS` - checked, with defaults filled in
+    fn new_game(ctx: &mut GameContext) -> Self::State;
+
+    /// Checks the schema can't express, run on the full settings before any room opens
+    /// e.g. a fleet that has to fit the board - every value already matches its `SettingSpec`
+    fn check_settings(_settings: &Settings) -> Result<(), String> {
+        Ok(())
+    }
+
     /// Handle player input and update game state
-    /// Returns a message to send back to the player
-    fn handle_input(input: &Self::Input, state: &mut Self::State, player_name: &str) -> String;
-    
+    /// `ctx.player()` is who sent it - answer them with `ctx.reply`
+    fn handle_input(input: &Self::Input, state: &mut Self::State, ctx: &mut GameContext);
+
     /// Handle player joining the game - separate from input handling!
-    fn on_player_join(state: &mut Self::State, player_name: &str) -> String {
+    fn on_player_join(_state: &mut Self::State, ctx: &mut GameContext) {
         // Default implementation - games can override this
-        format!("{} joined the game!", player_name)
+        let announcement = format!("{} joined the game!", ctx.player_name());
+        ctx.broadcast(announcement);
     }
-    
+
+    /// Handle player leaving the game - skip their turn, forfeit them, etc.
+    fn on_player_leave(_state: &mut Self::State, ctx: &mut GameContext) {
+        let announcement = format!("{} left the game.", ctx.player_name());
+        ctx.broadcast(announcement);
+    }
+
+    /// Advance the game by one server tick - called `TICK_RATE` times a second
+    /// Return true if the state changed so players get a fresh view
+    /// Ticks that return false aren't recorded - they mustn't touch the state or draw from `ctx.rng`
+    fn tick(_state: &mut Self::State, _ctx: &mut GameContext) -> bool {
+        false
+    }
+
+    /// A timer scheduled through `ctx.timers` went off - e.g. skip a slow player's turn
+    fn on_timer(_state: &mut Self::State, _key: &str, _ctx: &mut GameContext) {}
+
+    /// Project the state for a single viewer - strip anything they shouldn't see!
+    /// The server only ever sends views, so secrets never leave the host.
+    fn view(state: &Self::State, viewer: &Player) -> Self::View;
+
+    /// What spectators see - defaults to the view of someone who isn't seated
+    fn spectator_view(state: &Self::State) -> Self::View {
+        Self::view(state, &Player::nobody())
+    }
+
     /// Parse line input into game commands - game developer controls this
     fn parse_line(line: &str) -> Option<Self::Input>;
+
+    /// Turn a single keypress into a command - only called when `INPUT_MODE` uses keys
+    /// Arrows, function keys and modifiers all come through, return None to ignore the key
+    fn map_key(_key: &KeyEvent) -> Option<Self::Input> {
+        None
+    }
+
+    /// Computer player for empty seats - `host --bots N` and the host's `/bot` need one
+    fn bot() -> Option<Box<dyn Bot<Self>>> {
+        None
+    }
 }
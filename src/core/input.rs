@@ -0,0 +1,134 @@
+/// Input modes and key chords - how keystrokes turn into game input
+use std::fmt;
+use std::str::FromStr;
+
+pub use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// How a game wants its keystrokes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputMode {
+    /// Type a line, send it with Enter through `parse_line` - turn-based games
+    Line,
+    /// Every key goes straight to `map_key` - real-time games, arrows and WASD
+    Keys,
+    /// Keys `map_key` understands are sent right away, everything else builds a line
+    Both,
+}
+
+impl InputMode {
+    pub fn uses_keys(self) -> bool {
+        matches!(self, InputMode::Keys | InputMode::Both)
+    }
+
+    pub fn uses_line(self) -> bool {
+        matches!(self, InputMode::Line | InputMode::Both)
+    }
+}
+
+/// A key plus modifiers, like Ctrl-C - used for the quit binding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// Ctrl plus a letter
+    pub const fn ctrl(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    /// A key on its own
+    pub const fn key(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    /// Shift is ignored for characters - terminals already fold it into the char
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        let mut modifiers = event.modifiers;
+        if matches!(event.code, KeyCode::Char(_)) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        let code = match event.code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => KeyCode::Char(c.to_ascii_lowercase()),
+            code => code,
+        };
+        code == self.code && modifiers == self.modifiers
+    }
+}
+
+/// Quit bindings games get unless they pick their own
+pub const DEFAULT_QUIT_KEYS: &[KeyChord] = &[KeyChord::ctrl('c'), KeyChord::key(KeyCode::Esc)];
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Delete"),
+            KeyCode::Up => write!(f, "Up"),
+            KeyCode::Down => write!(f, "Down"),
+            KeyCode::Left => write!(f, "Left"),
+            KeyCode::Right => write!(f, "Right"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Parse chords like `ctrl-c`, `esc`, `alt-x` or `f10`
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<String> = s.split(['-', '+']).map(|p| p.trim().to_lowercase()).collect();
+        let key = parts.pop().filter(|k| !k.is_empty()).ok_or_else(|| format!("Empty key chord '{}'", s))?;
+
+        for part in parts {
+            match part.as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" => modifiers |= KeyModifiers::ALT,
+                "shift" => modifiers |= KeyModifiers::SHIFT,
+                other => return Err(format!("Unknown modifier '{}' in '{}'", other, s)),
+            }
+        }
+
+        let code = match key.as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            f if f.len() > 1 && f.starts_with('f') => {
+                let n = f[1..].parse().map_err(|_| format!("Unknown key '{}' in '{}'", f, s))?;
+                KeyCode::F(n)
+            }
+            c if c.chars().count() == 1 => KeyCode::Char(c.chars().next().unwrap_or_default()),
+            other => return Err(format!("Unknown key '{}' in '{}'", other, s)),
+        };
+
+        Ok(Self { code, modifiers })
+    }
+}
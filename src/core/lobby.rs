@@ -0,0 +1,294 @@
+/// Lobby server - one address, many rooms, any registered game
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::RwLock;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::core::discovery::{self, Announcement};
+use crate::core::protocol::{self, ClientMessage, ErrorCode, GameMetadata, ServerInfo, ServerMessage};
+use crate::core::recording;
+use crate::core::registry::GameRegistry;
+use crate::core::room::{Room, RoomInfo};
+use crate::core::websocket::accept_connection;
+use crate::core::websocket_host::HostOptions;
+
+/// How long a room may sit empty before it gets garbage-collected
+const EMPTY_ROOM_TTL: Duration = Duration::from_secs(60);
+
+/// How often the lobby sweeps for empty rooms
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How many room ids the LAN beacon names before summarising the rest
+const ANNOUNCED_ROOMS: usize = 8;
+
+/// A hosted room plus the bookkeeping the sweeper needs
+struct LobbyRoom {
+    room: Arc<dyn Room>,
+    empty_since: Option<Instant>,
+}
+
+/// Shared lobby state - rooms come and go, the registry builds them
+struct Lobby {
+    name: String,
+    registry: GameRegistry,
+    rooms: RwLock<HashMap<String, LobbyRoom>>,
+    next_room: AtomicUsize,
+    options: HostOptions,
+}
+
+impl Lobby {
+    async fn room_list(&self) -> Vec<RoomInfo> {
+        let rooms: Vec<Arc<dyn Room>> = self.rooms.read().await
+            .values()
+            .map(|r| Arc::clone(&r.room))
+            .collect();
+
+        let mut infos = Vec::with_capacity(rooms.len());
+        for room in rooms {
+            infos.push(room.info().await);
+        }
+        infos.sort_by(|a, b| a.id.cmp(&b.id));
+        infos
+    }
+
+    async fn create_room(&self, game: &str) -> Result<Arc<dyn Room>, String> {
+        let number = self.next_room.fetch_add(1, Ordering::Relaxed) + 1;
+        let room_id = format!("{}-{}", game.to_lowercase(), number);
+
+        // Every room gets its own seed so two rooms of the same game don't play out identically
+        let options = HostOptions {
+            seed: self.options.seed.map(|seed| seed.wrapping_add(number as u64 - 1)),
+            record: self.options.record.as_deref().map(|path| recording::room_path(path, &room_id)),
+            save: self.options.save.as_deref().map(|path| recording::room_path(path, &room_id)),
+            resume: None,
+            ..self.options.clone()
+        };
+        let room = self.registry.create_room(game, room_id.clone(), &options)
+            .map_err(|e| e.to_string())?;
+
+        println!("🏠 Created room {} ({})", room_id, game);
+        for _ in 0..self.options.bots {
+            if let Err(e) = room.add_bot().await {
+                println!("🤖 No bot for room {}: {}", room_id, e.message);
+                break;
+            }
+        }
+        self.rooms.write().await.insert(room_id, LobbyRoom {
+            room: Arc::clone(&room),
+            empty_since: Some(Instant::now()),
+        });
+        Ok(room)
+    }
+
+    /// Handshake info - every game the lobby can host and the rooms open right now
+    async fn server_info(&self) -> ServerInfo {
+        ServerInfo::Lobby {
+            name: self.name.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            games: self.registry.list_games().into_iter()
+                .map(|g| GameMetadata { name: g.name, version: g.version, description: g.description })
+                .collect(),
+            rooms: self.room_list().await,
+        }
+    }
+
+    async fn room(&self, room_id: &str) -> Option<Arc<dyn Room>> {
+        self.rooms.read().await.get(room_id).map(|r| Arc::clone(&r.room))
+    }
+
+    /// Drop rooms that have been empty for longer than the TTL
+    async fn sweep(&self) {
+        let mut counts = Vec::new();
+        for (id, entry) in self.rooms.read().await.iter() {
+            // Watchers keep a room alive too - bots on their own don't
+            counts.push((id.clone(), entry.room.human_count().await));
+        }
+
+        let now = Instant::now();
+        let mut rooms = self.rooms.write().await;
+        for (id, count) in counts {
+            let Some(entry) = rooms.get_mut(&id) else { continue };
+
+            if count > 0 {
+                entry.empty_since = None;
+                continue;
+            }
+
+            let empty_since = *entry.empty_since.get_or_insert(now);
+            if now.duration_since(empty_since) >= EMPTY_ROOM_TTL {
+                println!("🧹 Closing empty room {}", id);
+                rooms.remove(&id);
+            }
+        }
+    }
+}
+
+/// WebSocket lobby server - clients list, create and join rooms over the protocol
+pub struct LobbyServer {
+    listener: TcpListener,
+    lobby: Arc<Lobby>,
+    options: HostOptions,
+}
+
+impl LobbyServer {
+    pub async fn new(addr: &str, registry: GameRegistry, options: HostOptions) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let listener = TcpListener::bind(addr).await?;
+
+        Ok(Self {
+            listener,
+            lobby: Arc::new(Lobby {
+                name: "lanterm lobby".to_string(),
+                registry,
+                rooms: RwLock::new(HashMap::new()),
+                next_room: AtomicUsize::new(0),
+                options: options.clone(),
+            }),
+            options,
+        })
+    }
+
+    /// Start the lobby - accepts connections and sweeps empty rooms
+    pub async fn run(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let local_addr = self.listener.local_addr()?;
+        println!("WebSocket lobby listening on {}", local_addr);
+
+        // Let players on the LAN find us
+        if self.options.announce {
+            let lobby = Arc::clone(&self.lobby);
+            let id = Uuid::new_v4().to_string();
+            tokio::spawn(async move {
+                discovery::run_beacon(local_addr.ip(), || async {
+                    let rooms = lobby.room_list().await;
+                    // Name a few rooms, not all - the beacon has to fit in one datagram
+                    let ids: Vec<&str> = rooms.iter().take(ANNOUNCED_ROOMS).map(|r| r.id.as_str()).collect();
+                    let description = match rooms.len() {
+                        0 => "Lobby - no rooms yet".to_string(),
+                        n if n > ANNOUNCED_ROOMS => format!("Lobby - rooms: {} and {} more", ids.join(", "), n - ANNOUNCED_ROOMS),
+                        _ => format!("Lobby - rooms: {}", ids.join(", ")),
+                    };
+                    Announcement {
+                        id: id.clone(),
+                        name: lobby.name.clone(),
+                        version: env!("CARGO_PKG_VERSION").to_string(),
+                        description,
+                        players: rooms.iter().map(|r| r.players).sum(),
+                        spectators: rooms.iter().map(|r| r.spectators).sum(),
+                        port: local_addr.port(),
+                    }
+                }).await
+            });
+        }
+
+        // Garbage-collect empty rooms in the background
+        let lobby = Arc::clone(&self.lobby);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                lobby.sweep().await;
+            }
+        });
+
+        while let Ok((stream, addr)) = self.listener.accept().await {
+            println!("New connection from {}", addr);
+
+            tokio::spawn(Self::handle_connection(stream, Arc::clone(&self.lobby)));
+        }
+
+        Ok(())
+    }
+
+    /// Handle a lobby connection - lobby frames are handled here, game frames go to the room
+    async fn handle_connection(stream: TcpStream, lobby: Arc<Lobby>) {
+        // Handshake with the lobby overview instead of a single game's metadata
+        let Some(mut conn) = accept_connection(stream, lobby.server_info().await).await else {
+            return;
+        };
+
+        let session_id = Uuid::new_v4().to_string();
+        let mut current_room: Option<Arc<dyn Room>> = None;
+        let session_sender = conn.sender.clone();
+
+        let reply = |msg: ServerMessage<()>| {
+            let _ = session_sender.send(protocol::encode(&msg));
+        };
+        let error = |code: ErrorCode, message: String| {
+            let _ = session_sender.send(protocol::error_frame(code, message));
+        };
+
+        while let Some(text) = conn.next_text().await {
+            // Only the envelope is parsed here - game inputs are the room's business
+            let frame = match serde_json::from_str::<protocol::UntypedClientMessage>(&text) {
+                Ok(frame) => frame,
+                Err(e) => {
+                    error(ErrorCode::MalformedFrame, format!("Could not read frame: {}", e));
+                    continue;
+                }
+            };
+
+            match frame {
+                ClientMessage::ListRooms => {
+                    reply(ServerMessage::RoomList(lobby.room_list().await));
+                }
+
+                ClientMessage::CreateRoom { game } => {
+                    match lobby.create_room(&game).await {
+                        Ok(room) => reply(ServerMessage::RoomCreated(room.info().await)),
+                        Err(e) => error(ErrorCode::UnknownGame, e),
+                    }
+                }
+
+                ClientMessage::JoinRoom { room_id } => {
+                    if let Some(room) = current_room.take() {
+                        room.leave(&session_id).await;
+                    }
+
+                    let Some(room) = lobby.room(&room_id).await else {
+                        error(ErrorCode::UnknownRoom, format!("Room '{}' not found", room_id));
+                        continue;
+                    };
+
+                    // Same checks a single-game server does at the handshake
+                    let game = room.metadata();
+                    if let Err(reject) = protocol::negotiate(&conn.hello, &ServerInfo::Game(game.clone())) {
+                        error(ErrorCode::IncompatibleRoom, reject.message);
+                        continue;
+                    }
+
+                    reply(ServerMessage::RoomEntered { room: room.info().await, game });
+                    current_room = Some(room);
+                }
+
+                ClientMessage::LeaveRoom => {
+                    if let Some(room) = current_room.take() {
+                        room.leave(&session_id).await;
+                    }
+                }
+
+                ClientMessage::Hello(_) => {
+                    error(ErrorCode::UnexpectedFrame, "Handshake already done".to_string());
+                }
+
+                ClientMessage::PlayerJoin { .. } | ClientMessage::Spectate { .. } | ClientMessage::PlayerInput(_) | ClientMessage::Chat(_) | ClientMessage::PlayerLeave => {
+                    // Game frames belong to the room
+                    let Some(room) = current_room.as_ref() else {
+                        error(ErrorCode::NotInRoom, "Join a room first".to_string());
+                        continue;
+                    };
+                    if !room.handle_text(&session_id, &session_sender, &conn.capabilities, &text).await {
+                        room.leave(&session_id).await;
+                        current_room = None;
+                    }
+                }
+            }
+        }
+
+        // Gone without a `LeaveRoom` - the room decides how long to hold the seat
+        if let Some(room) = current_room {
+            room.disconnect(&session_id).await;
+        }
+    }
+}
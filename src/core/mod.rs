@@ -1,8 +1,20 @@
 pub mod game;
+pub mod context;   // Seeded RNG and timers handed to every game hook
+pub mod bot;       // Computer players for empty seats
 pub mod registry;
 pub mod renderer;  // Auto-injection traits
 pub mod terminal;  // Terminal context wrapper
+pub mod input;     // Input modes, key mapping and quit chords
 
 // WebSocket-based architecture (clean and event-driven!)
 pub mod websocket;
+pub mod protocol;  // Typed, versioned frames and the hello/welcome handshake
 pub mod websocket_host;
+pub mod room;      // One game instance - state and sessions
+pub mod lobby;     // Many rooms behind one address
+pub mod discovery; // UDP beacons for finding servers on the LAN
+pub mod timers;    // One-shot timers games can arm from their hooks
+pub mod chat;      // Room chat history and rate limiting
+pub mod recording; // JSON Lines session logs for replay and verification
+pub mod save;      // Saved games for `host --resume`
+pub mod settings;  // Per-game settings schemas for `host --set`
@@ -0,0 +1,465 @@
+/// Wire protocol - every frame lanterm clients and servers exchange, versioned and typed
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use serde::{Serialize, Deserialize};
+use serde::de::IgnoredAny;
+use chrono::{DateTime, Utc};
+use tokio::time::Duration;
+use tokio_tungstenite::tungstenite::{self, Message};
+
+use crate::core::context::PlayerId;
+use crate::core::room::RoomInfo;
+
+/// Bumped whenever a frame changes shape - peers on different versions refuse each other
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// How long a server waits for `Hello` before giving up on a connection
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Optional protocol features - both sides announce theirs, the `Welcome` carries the overlap
+/// Unknown capabilities from newer peers deserialize as `Unknown` and are simply dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Capability {
+    /// Re-present a resume token to reclaim a dropped seat
+    Resume,
+    /// Understands lobby frames (room lists, creating and entering rooms)
+    Lobby,
+    /// Can watch a game without taking a seat
+    Spectate,
+    /// Room chat alongside the game
+    Chat,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Everything this build of lanterm supports - clients and servers alike
+pub const CAPABILITIES: &[Capability] = &[Capability::Resume, Capability::Lobby, Capability::Spectate, Capability::Chat];
+
+/// How a session takes part in a room
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Role {
+    /// Holds a seat and sends input
+    #[default]
+    Player,
+    /// Watches without a seat - never counted as a player, never runs game hooks
+    Spectator,
+}
+
+/// One chat line - relayed to the whole room and kept in its history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub from: String,
+    pub role: Role,
+    pub text: String,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// What a hosted game is - sent in `Welcome` and when entering a lobby room
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameMetadata {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+}
+
+/// First frame a client sends - nothing else is accepted before it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hello {
+    pub protocol_version: u32,
+    /// lanterm version of the client build
+    pub client_version: String,
+    /// Game the client wants to play - `None` while just detecting what's hosted
+    pub game: Option<String>,
+    /// Version of that game the client was built with
+    pub game_version: Option<String>,
+    pub capabilities: Vec<Capability>,
+}
+
+impl Hello {
+    /// Hello from this build - pass the game when the client is about to play it
+    pub fn new(game: Option<&GameMetadata>) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            client_version: env!("CARGO_PKG_VERSION").to_string(),
+            game: game.map(|g| g.name.clone()),
+            game_version: game.map(|g| g.version.clone()),
+            capabilities: CAPABILITIES.to_vec(),
+        }
+    }
+}
+
+/// What's on the other end of the socket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ServerInfo {
+    /// A single game on its own port
+    Game(GameMetadata),
+    /// A lobby hosting rooms for any of `games`
+    Lobby { name: String, version: String, games: Vec<GameMetadata>, rooms: Vec<RoomInfo> },
+}
+
+/// Successful handshake reply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Welcome {
+    pub protocol_version: u32,
+    pub server: ServerInfo,
+    /// Capabilities both sides support
+    pub capabilities: Vec<Capability>,
+}
+
+/// Why a handshake was refused
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RejectReason {
+    /// First frame wasn't `Hello` - usually a client from before the handshake existed
+    HandshakeRequired,
+    ProtocolMismatch { server: u32, client: u32 },
+    /// Client wants a different game than the one hosted here
+    WrongGame { hosted: String, requested: String },
+    GameVersionMismatch { hosted: String, requested: String },
+    /// Client lacks a capability this server can't work without
+    MissingCapability(Capability),
+}
+
+/// Handshake refusal - the server closes the connection right after sending it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reject {
+    pub reason: RejectReason,
+    pub message: String,
+}
+
+impl Reject {
+    pub fn new(reason: RejectReason) -> Self {
+        let message = match &reason {
+            RejectReason::HandshakeRequired => {
+                "Expected a Hello frame first - this client is too old for the server".to_string()
+            }
+            RejectReason::ProtocolMismatch { server, client } => {
+                format!("Protocol v{} is not supported - the server speaks v{}", client, server)
+            }
+            RejectReason::WrongGame { hosted, requested } => {
+                format!("This server hosts {}, not {}", hosted, requested)
+            }
+            RejectReason::GameVersionMismatch { hosted, requested } => {
+                format!("Game version {} is incompatible with the hosted {}", requested, hosted)
+            }
+            RejectReason::MissingCapability(capability) => {
+                format!("This server requires the {:?} capability", capability)
+            }
+        };
+        Self { reason, message }
+    }
+}
+
+/// Kinds of errors reported after the handshake - the connection stays open
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorCode {
+    /// Frame wasn't valid JSON or didn't match the protocol
+    MalformedFrame,
+    /// Frame is valid but makes no sense right now (e.g. `Hello` twice)
+    UnexpectedFrame,
+    /// Input sent before joining
+    NotJoined,
+    /// Every seat is taken - watching is still possible
+    RoomFull,
+    /// Spectators can't send game input
+    Spectating,
+    /// Chatting too fast - wait a few seconds
+    RateLimited,
+    /// Lobby frame sent to a single-game server
+    NotALobby,
+    /// Game frame sent in a lobby before entering a room
+    NotInRoom,
+    UnknownRoom,
+    UnknownGame,
+    /// Room runs a different game or version than the client declared in `Hello`
+    IncompatibleRoom,
+    /// Only the host can do that - or the server isn't set up for it (e.g. `/save` without `--save`)
+    NotAllowed,
+}
+
+/// Typed error frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ProtocolError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+/// Frames a client sends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClientMessage<Input> {
+    /// Handshake - must be the first frame
+    Hello(Hello),
+    /// Player joining the game - a valid resume token reclaims a dropped seat
+    PlayerJoin {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        resume_token: Option<String>,
+    },
+    /// Watch the game without a seat - the server answers with `Spectating`
+    Spectate { name: String },
+    /// Input from player
+    PlayerInput(Input),
+    /// Player (or spectator) leaving the game
+    PlayerLeave,
+    /// Say something to the room - never goes through the game's input parsing
+    Chat(String),
+    /// Lobby: ask for the current room list
+    ListRooms,
+    /// Lobby: create a new room running the named game
+    CreateRoom { game: String },
+    /// Lobby: enter a room - every following game frame goes to it
+    JoinRoom { room_id: String },
+    /// Lobby: leave the current room and return to the lobby
+    LeaveRoom,
+}
+
+/// Frames the server sends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ServerMessage<View> {
+    /// Handshake accepted
+    Welcome(Welcome),
+    /// Handshake refused - the connection closes next
+    Reject(Reject),
+    /// Join acknowledgement - keep the token to resume after a network drop
+    /// `name` is the display name the room settled on, which differs if ours was taken
+    Joined {
+        resume_token: String,
+        #[serde(default)]
+        player_id: PlayerId,
+        #[serde(default)]
+        name: String,
+    },
+    /// Spectate acknowledgement - state updates follow, input is refused
+    Spectating,
+    /// This player's view of the game state
+    StateUpdate(View),
+    /// Game notification
+    Message(String),
+    /// Someone in the room said something
+    Chat(ChatMessage),
+    /// Recent chat - sent on join so newcomers have context
+    ChatHistory(Vec<ChatMessage>),
+    /// Something went wrong with the last frame
+    Error(ProtocolError),
+    /// Lobby: current room list
+    RoomList(Vec<RoomInfo>),
+    /// Lobby: room was created - enter it with `JoinRoom`
+    RoomCreated(RoomInfo),
+    /// Lobby: now inside this room - game frames go to it from here on
+    RoomEntered { room: RoomInfo, game: GameMetadata },
+}
+
+/// Client frame with the game input left unparsed - lets lobbies route frames for any game
+pub type UntypedClientMessage = ClientMessage<IgnoredAny>;
+
+/// Server frame with the view left unparsed - enough to detect games and follow the lobby
+pub type UntypedServerMessage = ServerMessage<IgnoredAny>;
+
+/// Serialize a frame - our own types always serialize, so this never fails in practice
+pub fn encode<T: Serialize>(frame: &T) -> String {
+    serde_json::to_string(frame).unwrap_or_default()
+}
+
+/// Encode an error frame without knowing the game's view type
+pub fn error_frame(code: ErrorCode, message: impl Into<String>) -> String {
+    encode(&ServerMessage::<()>::Error(ProtocolError::new(code, message)))
+}
+
+/// Versions are compatible when their major parts match (minor too while still at 0.x)
+pub fn versions_compatible(a: &str, b: &str) -> bool {
+    fn key(version: &str) -> (String, Option<String>) {
+        let mut parts = version.trim_start_matches('v').split('.');
+        let major = parts.next().unwrap_or("0").to_string();
+        let minor = if major == "0" { parts.next().map(str::to_string) } else { None };
+        (major, minor)
+    }
+    key(a) == key(b)
+}
+
+/// Decide whether to accept a client - pure, so both server kinds share the rules
+pub fn negotiate(hello: &Hello, server: &ServerInfo) -> Result<Welcome, Reject> {
+    if hello.protocol_version != PROTOCOL_VERSION {
+        return Err(Reject::new(RejectReason::ProtocolMismatch {
+            server: PROTOCOL_VERSION,
+            client: hello.protocol_version,
+        }));
+    }
+
+    match server {
+        ServerInfo::Game(hosted) => {
+            if let Some(requested) = &hello.game {
+                if requested != &hosted.name {
+                    return Err(Reject::new(RejectReason::WrongGame {
+                        hosted: hosted.name.clone(),
+                        requested: requested.clone(),
+                    }));
+                }
+            }
+            if let Some(requested) = &hello.game_version {
+                if !versions_compatible(requested, &hosted.version) {
+                    return Err(Reject::new(RejectReason::GameVersionMismatch {
+                        hosted: hosted.version.clone(),
+                        requested: requested.clone(),
+                    }));
+                }
+            }
+        }
+        ServerInfo::Lobby { .. } => {
+            if !hello.capabilities.contains(&Capability::Lobby) {
+                return Err(Reject::new(RejectReason::MissingCapability(Capability::Lobby)));
+            }
+        }
+    }
+
+    let capabilities = CAPABILITIES
+        .iter()
+        .copied()
+        .filter(|c| hello.capabilities.contains(c))
+        .collect();
+
+    Ok(Welcome {
+        protocol_version: PROTOCOL_VERSION,
+        server: server.clone(),
+        capabilities,
+    })
+}
+
+/// Server side of the handshake - wait for `Hello`, answer `Welcome` or `Reject`
+/// Returns the client's hello and the negotiated capabilities when the connection may proceed
+pub async fn accept_handshake<S, R>(ws_sender: &mut S, ws_receiver: &mut R, server: ServerInfo) -> Option<(Hello, Vec<Capability>)>
+where
+    S: Sink<Message, Error = tungstenite::Error> + Unpin,
+    R: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+{
+    let first = tokio::time::timeout(HANDSHAKE_TIMEOUT, async {
+        while let Some(Ok(msg)) = ws_receiver.next().await {
+            match msg {
+                Message::Text(text) => return Some(text),
+                Message::Close(_) => return None,
+                _ => continue,
+            }
+        }
+        None
+    }).await.ok().flatten()?;
+
+    let verdict = match serde_json::from_str::<UntypedClientMessage>(&first) {
+        Ok(ClientMessage::Hello(hello)) => negotiate(&hello, &server).map(|welcome| (hello, welcome)),
+        _ => Err(Reject::new(RejectReason::HandshakeRequired)),
+    };
+
+    match verdict {
+        Ok((hello, welcome)) => {
+            let capabilities = welcome.capabilities.clone();
+            let frame = encode(&ServerMessage::<()>::Welcome(welcome));
+            ws_sender.send(Message::Text(frame)).await.ok()?;
+            Some((hello, capabilities))
+        }
+        Err(reject) => {
+            eprintln!("Rejected client: {}", reject.message);
+            let _ = ws_sender.send(Message::Text(encode(&ServerMessage::<()>::Reject(reject)))).await;
+            let _ = ws_sender.close().await;
+            None
+        }
+    }
+}
+
+/// Client side of the handshake - send `Hello`, wait for `Welcome`
+/// A `Reject` becomes an error carrying the server's explanation
+pub async fn client_handshake<S, R>(ws_sender: &mut S, ws_receiver: &mut R, hello: Hello) -> Result<Welcome, Box<dyn std::error::Error + Send + Sync>>
+where
+    S: Sink<Message, Error = tungstenite::Error> + Unpin,
+    R: Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+{
+    let frame = encode(&ClientMessage::<()>::Hello(hello));
+    ws_sender.send(Message::Text(frame)).await?;
+
+    while let Some(msg_result) = ws_receiver.next().await {
+        if let Message::Text(text) = msg_result? {
+            match serde_json::from_str::<UntypedServerMessage>(&text) {
+                Ok(ServerMessage::Welcome(welcome)) => return Ok(welcome),
+                Ok(ServerMessage::Reject(reject)) => return Err(format!("Server refused the connection: {}", reject.message).into()),
+                _ => return Err("Server did not answer the handshake - is it running an older lanterm?".into()),
+            }
+        }
+    }
+
+    Err("Server closed the connection during the handshake".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hangman(version: &str) -> GameMetadata {
+        GameMetadata { name: "Hangman".to_string(), version: version.to_string(), description: String::new() }
+    }
+
+    fn hello(game: Option<&GameMetadata>, capabilities: &[Capability]) -> Hello {
+        Hello { capabilities: capabilities.to_vec(), ..Hello::new(game) }
+    }
+
+    fn reason(result: Result<Welcome, Reject>) -> RejectReason {
+        result.map(|_| ()).unwrap_err().reason
+    }
+
+    #[test]
+    fn versions_compare_by_major() {
+        assert!(versions_compatible("1.0.0", "1.4.2"));
+        assert!(versions_compatible("v2.0.0", "2.1.0"));
+        assert!(!versions_compatible("1.0.0", "2.0.0"));
+    }
+
+    #[test]
+    fn zero_versions_compare_by_minor() {
+        assert!(versions_compatible("0.3.0", "0.3.9"));
+        assert!(!versions_compatible("0.3.0", "0.4.0"));
+    }
+
+    #[test]
+    fn welcome_carries_the_capability_overlap() {
+        let welcome = negotiate(&hello(None, &[Capability::Chat, Capability::Unknown]), &ServerInfo::Game(hangman("1.0.0"))).unwrap();
+        assert_eq!(welcome.capabilities, vec![Capability::Chat]);
+    }
+
+    #[test]
+    fn wrong_game_is_rejected() {
+        let other = GameMetadata { name: "Battleship".to_string(), ..hangman("1.0.0") };
+        assert_eq!(
+            reason(negotiate(&Hello::new(Some(&other)), &ServerInfo::Game(hangman("1.0.0")))),
+            RejectReason::WrongGame { hosted: "Hangman".to_string(), requested: "Battleship".to_string() },
+        );
+    }
+
+    #[test]
+    fn incompatible_game_version_is_rejected() {
+        assert_eq!(
+            reason(negotiate(&Hello::new(Some(&hangman("1.2.0"))), &ServerInfo::Game(hangman("2.0.0")))),
+            RejectReason::GameVersionMismatch { hosted: "2.0.0".to_string(), requested: "1.2.0".to_string() },
+        );
+        assert_eq!(
+            reason(negotiate(&Hello::new(Some(&hangman("0.1.0"))), &ServerInfo::Game(hangman("0.2.0")))),
+            RejectReason::GameVersionMismatch { hosted: "0.2.0".to_string(), requested: "0.1.0".to_string() },
+        );
+        assert!(negotiate(&Hello::new(Some(&hangman("0.2.1"))), &ServerInfo::Game(hangman("0.2.0"))).is_ok());
+    }
+
+    #[test]
+    fn lobby_requires_the_lobby_capability() {
+        let lobby = ServerInfo::Lobby { name: "LAN".to_string(), version: "0.1.0".to_string(), games: vec![], rooms: vec![] };
+        assert_eq!(reason(negotiate(&hello(None, &[Capability::Chat]), &lobby)), RejectReason::MissingCapability(Capability::Lobby));
+        assert!(negotiate(&Hello::new(None), &lobby).is_ok());
+    }
+
+    #[test]
+    fn protocol_mismatch_is_rejected() {
+        let old = Hello { protocol_version: PROTOCOL_VERSION + 1, ..Hello::new(None) };
+        assert_eq!(
+            reason(negotiate(&old, &ServerInfo::Game(hangman("1.0.0")))),
+            RejectReason::ProtocolMismatch { server: PROTOCOL_VERSION, client: PROTOCOL_VERSION + 1 },
+        );
+    }
+}
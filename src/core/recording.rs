@@ -0,0 +1,246 @@
+/// Session recordings - a JSON Lines log of everything that happened in a room
+///
+/// The first line is a `Header` with the game, seed and starting state. Every line after it is an
+/// `Entry` - who did what, when, and the state it left behind - so `lanterm replay` can show the
+/// match without the original players, and `verify` can check the game still plays out the same.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::time::Duration;
+
+use crate::core::context::{self, GameContext, Player, Settings};
+use crate::core::game::WebSocketGame;
+use crate::core::settings;
+
+/// First line of a recording - enough to rebuild the game from scratch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Header {
+    pub game: String,
+    pub version: String,
+    pub room: String,
+    /// Seed the room's RNG started from
+    pub seed: u64,
+    /// What the host passed with `--set` - `new_game` needs them to start the same way again
+    #[serde(default)]
+    pub settings: Settings,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// Picked up from a save instead of `new_game` - `seed` is the one the RNG resumed with
+    #[serde(default)]
+    pub resumed: bool,
+    /// State `new_game` returned, or the one the save held
+    pub state: Value,
+}
+
+/// What made the state change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Join { player: Player },
+    Input { player: Player, input: Value },
+    Leave { player: Player },
+    /// One clock step - a tick if the game has a tick rate, then the timers that fired, in order
+    Clock { tick: bool, timers: Vec<String> },
+}
+
+impl Event {
+    /// Who triggered it - `None` for the clock
+    pub fn player(&self) -> Option<&Player> {
+        match self {
+            Event::Join { player } | Event::Input { player, .. } | Event::Leave { player } => Some(player),
+            Event::Clock { .. } => None,
+        }
+    }
+
+    /// One line for humans - shown under the board during a replay
+    pub fn describe(&self) -> String {
+        match self {
+            Event::Join { player } => format!("{} joined", player.name),
+            Event::Input { player, input } => format!("{}: {}", player.name, input),
+            Event::Leave { player } => format!("{} left", player.name),
+            Event::Clock { tick, timers } if timers.is_empty() && *tick => "⏱️ tick".to_string(),
+            Event::Clock { timers, .. } => format!("⏱️ timer {}", timers.join(", ")),
+        }
+    }
+}
+
+/// One line of a recording after the header
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// Milliseconds since the room opened - the `ctx.now()` the hook saw
+    pub at_ms: u64,
+    #[serde(flatten)]
+    pub event: Event,
+    /// State right after the hook ran
+    pub state: Value,
+}
+
+/// Appends a room's history to a file as it happens - every line is flushed so a crash keeps the log
+pub struct Recorder {
+    path: PathBuf,
+    out: BufWriter<File>,
+    /// Set after the first write error - one warning is enough
+    failed: bool,
+}
+
+impl Recorder {
+    /// Start a fresh recording - an existing file is overwritten
+    pub fn create(path: &Path, header: &Header) -> io::Result<Self> {
+        let mut recorder = Self {
+            path: path.to_path_buf(),
+            out: BufWriter::new(File::create(path)?),
+            failed: false,
+        };
+        recorder.write_line(header)?;
+        Ok(recorder)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&mut self, entry: &Entry) {
+        if self.failed {
+            return;
+        }
+        if let Err(e) = self.write_line(entry) {
+            eprintln!("⚠️  Recording to {} stopped: {}", self.path.display(), e);
+            self.failed = true;
+        }
+    }
+
+    fn write_line<T: Serialize>(&mut self, line: &T) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, line)?;
+        self.out.write_all(b"\n")?;
+        self.out.flush()
+    }
+}
+
+/// A recording read back from disk
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub header: Header,
+    pub entries: Vec<Entry>,
+}
+
+impl Recording {
+    /// Read a recording - a half-written last line (the host died mid-write) is dropped
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let file = File::open(path).map_err(|e| format!("Couldn't open {}: {}", path.display(), e))?;
+        let lines: Vec<String> = BufReader::new(file).lines().collect::<Result<_, _>>()?;
+        let mut lines = lines.iter().enumerate().filter(|(_, line)| !line.trim().is_empty()).peekable();
+
+        let Some((_, first)) = lines.next() else {
+            return Err(format!("{} is empty", path.display()).into());
+        };
+        let header: Header = serde_json::from_str(first)
+            .map_err(|e| format!("{} doesn't start with a recording header: {}", path.display(), e))?;
+
+        let mut entries = Vec::new();
+        while let Some((number, line)) = lines.next() {
+            match serde_json::from_str::<Entry>(line) {
+                Ok(entry) => entries.push(entry),
+                Err(_) if lines.peek().is_none() => break,
+                Err(e) => return Err(format!("{} line {}: {}", path.display(), number + 1, e).into()),
+            }
+        }
+        Ok(Self { header, entries })
+    }
+
+    /// How long the session ran, up to its last event
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.entries.last().map(|e| e.at_ms).unwrap_or(0))
+    }
+}
+
+/// Where a lobby room records - `games.jsonl` becomes `games-hangman-1.jsonl`
+pub fn room_path(path: &Path, room_id: &str) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("session");
+    let name = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}-{}.{}", stem, room_id, ext),
+        None => format!("{}-{}", stem, room_id),
+    };
+    path.with_file_name(name)
+}
+
+/// Re-run every recorded event through the game's hooks from the recorded seed
+/// Returns how many events matched, or where the state first diverged
+pub fn verify<G: WebSocketGame>(recording: &Recording) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let header = &recording.header;
+    if header.game != G::NAME {
+        return Err(format!("Recording is of {}, not {}", header.game, G::NAME).into());
+    }
+    if header.version != G::VERSION {
+        println!("⚠️  Recorded with {} v{}, verifying against v{}", header.game, header.version, G::VERSION);
+    }
+
+    let mut rng = context::seeded_rng(header.seed);
+    let mut state = if header.resumed {
+        serde_json::from_value(header.state.clone())?
+    } else {
+        // A hand-edited header could carry settings the game would never have been hosted with
+        let settings = settings::resolve::<G>(&header.settings)
+            .map_err(|e| format!("Recorded settings don't work for {} v{}: {}", G::NAME, G::VERSION, e))?;
+        let mut ctx = GameContext::new(&mut rng, Duration::ZERO).with_settings(settings);
+        let state = G::new_game(&mut ctx);
+        compare(&state, &header.state, "new_game (line 1)")?;
+        state
+    };
+
+    for (index, entry) in recording.entries.iter().enumerate() {
+        let now = Duration::from_millis(entry.at_ms);
+        let mut ctx = match entry.event.player() {
+            Some(player) => GameContext::for_player(&mut rng, now, player.clone()),
+            None => GameContext::new(&mut rng, now),
+        };
+
+        match &entry.event {
+            Event::Join { .. } => G::on_player_join(&mut state, &mut ctx),
+            Event::Input { input, .. } => {
+                let input: G::Input = serde_json::from_value(input.clone())
+                    .map_err(|e| format!("Line {}: input doesn't fit this version of {}: {}", index + 2, G::NAME, e))?;
+                G::handle_input(&input, &mut state, &mut ctx);
+            }
+            Event::Leave { .. } => G::on_player_leave(&mut state, &mut ctx),
+            Event::Clock { tick, timers } => {
+                if *tick {
+                    G::tick(&mut state, &mut ctx);
+                }
+                for key in timers {
+                    G::on_timer(&mut state, key, &mut ctx);
+                }
+            }
+        }
+
+        compare(&state, &entry.state, &format!("'{}' (line {})", entry.event.describe(), index + 2))?;
+    }
+    Ok(recording.entries.len())
+}
+
+/// Error naming the first field that differs, if the states aren't the same
+fn compare<S: Serialize>(state: &S, recorded: &Value, after: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let replayed = serde_json::to_value(state)?;
+    match first_difference(&replayed, recorded, String::new()) {
+        None => Ok(()),
+        Some(path) => Err(format!("State diverged after {} at `{}`", after, if path.is_empty() { "." } else { &path }).into()),
+    }
+}
+
+fn first_difference(a: &Value, b: &Value, path: String) -> Option<String> {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys = a.keys().chain(b.keys().filter(|k| !a.contains_key(*k)));
+            keys.into_iter().find_map(|key| match (a.get(key), b.get(key)) {
+                (Some(x), Some(y)) => first_difference(x, y, format!("{}.{}", path, key)),
+                _ => Some(format!("{}.{}", path, key)),
+            })
+        }
+        (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+            a.iter().zip(b).enumerate().find_map(|(i, (x, y))| first_difference(x, y, format!("{}[{}]", path, i)))
+        }
+        _ if a == b => None,
+        _ => Some(path),
+    }
+}
@@ -1,30 +1,35 @@
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
 
 use async_trait::async_trait;
-use futures_util::StreamExt;
-use serde::{Serialize, Deserialize};
+use futures_util::{SinkExt, StreamExt};
 
+use crate::bench::{self, BenchOptions};
+use crate::core::context::Settings;
 use crate::core::game::WebSocketGame;
 use crate::core::renderer::GameRenderer;
-use crate::core::websocket_host::WebSocketGameHost;
-use crate::client::websocket_client::WebSocketGameClient;
+use crate::core::protocol::{self, Hello, Role, ServerInfo};
+use crate::core::input::KeyEvent;
+use crate::core::recording::{self, Recording};
+use crate::core::room::{GameRoom, Room};
+use crate::core::settings::{self, SettingSpec};
+use crate::core::websocket_host::{HostOptions, WebSocketGameHost};
+use crate::client::lobby::choose_room;
+use crate::client::headless::{self, ScriptOptions};
+use crate::client::replay::{self, ReplayOptions};
+use crate::client::websocket_client::{JoinOptions, WebSocketGameClient};
 
 /// Metadata about a game - extracted from game trait constants
 #[derive(Debug, Clone)]
 pub struct GameMetadata {
     pub name: String,
+    pub version: String,
     pub description: String,
     pub min_players: usize,
     pub max_players: usize,
-}
-
-/// WebSocket game message for detection
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum DetectionMessage {
-    GameMetadata { name: String, version: String, description: String },
-    PlayerJoin { name: String },
-    #[serde(other)]
-    Other,
+    /// What the host can set with `--set` - empty if the game has no options
+    pub settings: &'static [SettingSpec],
 }
 
 /// Auto-injected game registration - framework discovers everything from traits
@@ -33,11 +38,27 @@ pub trait GameRegistration: Send + Sync {
     /// Get metadata about this game
     fn metadata(&self) -> GameMetadata;
     
+    /// Check the host's settings against the game's schema - every setting comes back, defaults filled in
+    fn resolve_settings(&self, settings: &Settings) -> Result<Settings, String>;
+    
     /// Start hosting this game - framework auto-injects
-    async fn start_host(&self, addr: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    async fn start_host(&self, addr: &str, options: HostOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    
+    /// Create a fresh room for this game - lobbies host many of these
+    fn create_room(&self, room_id: String, options: &HostOptions) -> Arc<dyn Room>;
     
     /// Start client for this game - framework auto-injects renderer
-    async fn start_client(&self, addr: &str, name: String) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    /// `options` pick the lobby room, playing or watching, and the quit keys
+    async fn start_client(&self, addr: &str, name: String, options: JoinOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    
+    /// Join without a terminal - script lines in, server events out as JSON lines
+    async fn start_headless(&self, addr: &str, name: String, options: JoinOptions, script: &ScriptOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    
+    /// Load a server with synthetic clients and print what they measured
+    async fn bench(&self, options: &BenchOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    
+    /// Play back (or verify) a recorded session - framework auto-injects the renderer
+    fn replay(&self, recording: &Recording, options: &ReplayOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
 }
 
 /// Generic game registration that auto-injects renderer only
@@ -48,69 +69,139 @@ pub struct GenericGameRegistration<G, R> {
 impl<G, R> GenericGameRegistration<G, R>
 where
     G: WebSocketGame + Send + Sync + 'static,
-    R: GameRenderer<G::State> + 'static,
+    R: GameRenderer<G::View> + 'static,
 {
     pub fn new() -> Self {
         Self {
             _phantom: std::marker::PhantomData,
         }
     }
+    
+    /// Client declaring this game, set up from the join options
+    fn client(name: String, options: JoinOptions) -> WebSocketGameClient {
+        let mut client = WebSocketGameClient::new(name).with_game(protocol::GameMetadata {
+            name: G::NAME.to_string(),
+            version: G::VERSION.to_string(),
+            description: G::DESCRIPTION.to_string(),
+        })
+        .with_input_mode(G::INPUT_MODE)
+        .with_quit_keys(G::QUIT_KEYS.to_vec())
+        .with_quit_keys(options.quit_keys);
+        if let Some(room_id) = options.room {
+            client = client.with_room(room_id);
+        }
+        if options.role == Role::Spectator {
+            client = client.spectate();
+        }
+        client
+    }
+}
+
+impl<G, R> Default for GenericGameRegistration<G, R>
+where
+    G: WebSocketGame + Send + Sync + 'static,
+    R: GameRenderer<G::View> + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[async_trait]
 impl<G, R> GameRegistration for GenericGameRegistration<G, R>
 where
     G: WebSocketGame + Send + Sync + 'static,
-    R: GameRenderer<G::State> + 'static,
+    R: GameRenderer<G::View> + 'static,
 {
     /// Extract metadata from game trait constants - no factory needed!
     fn metadata(&self) -> GameMetadata {
         GameMetadata {
             name: G::NAME.to_string(),
+            version: G::VERSION.to_string(),
             description: G::DESCRIPTION.to_string(),
             min_players: G::MIN_PLAYERS,
             max_players: G::MAX_PLAYERS,
+            settings: G::SETTINGS,
         }
     }
     
+    /// Schema first, then the game's own `check_settings`
+    fn resolve_settings(&self, settings: &Settings) -> Result<Settings, String> {
+        settings::resolve::<G>(settings)
+    }
+    
     /// Framework auto-injects game hosting using trait constants
-    async fn start_host(&self, addr: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn start_host(&self, addr: &str, options: HostOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         println!("🚀 Starting {} WebSocket server...", G::NAME);
         WebSocketGameHost::<G>::start(
             addr, 
             G::NAME, 
-            G::DESCRIPTION
+            G::DESCRIPTION,
+            options
         ).await
     }
     
-    /// Framework auto-injects client with renderer - game controls input parsing
-    async fn start_client(&self, addr: &str, name: String) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let server_url = if addr.starts_with("ws://") || addr.starts_with("wss://") {
-            addr.to_string()
-        } else {
-            format!("ws://{}", addr)
+    /// Framework auto-injects a room with fresh game state
+    fn create_room(&self, room_id: String, options: &HostOptions) -> Arc<dyn Room> {
+        let metadata = protocol::GameMetadata {
+            name: G::NAME.to_string(),
+            version: G::VERSION.to_string(),
+            description: G::DESCRIPTION.to_string(),
         };
-        
-        let mut client = WebSocketGameClient::new(name.clone());
+        let room = Arc::new(GameRoom::<G>::new(room_id, metadata, options.clone()));
+        GameRoom::start_clock(&room);
+        room
+    }
+    
+    /// Framework auto-injects client with renderer - game controls input parsing
+    async fn start_client(&self, addr: &str, name: String, options: JoinOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let server_url = ws_url(addr);
+        let mut client = Self::client(name.clone(), options);
         
         // Auto-inject renderer only - game controls input parsing!
         let renderer = R::new(name.clone());
         
-        println!("🎮 Connecting to {} WebSocket game! Press 'q' to quit.", G::NAME);
+        println!("🎮 Connecting to {} WebSocket game!", G::NAME);
         
-        client.connect_and_play::<G::State, G::Input, _, _>(
+        client.connect_and_play::<G::View, G::Input, _, _, _>(
             &server_url,
-            move |state: &G::State| {
-                // Auto-injected renderer handles all UI with TerminalContext
-                let mut ctx = crate::core::terminal::TerminalContext::new();
-                renderer.render(state, &mut ctx);
+            move |view: &G::View, ctx: &mut crate::core::terminal::TerminalContext| {
+                // Auto-injected renderer draws into the framework's game viewport
+                renderer.render(view, ctx);
             },
             |line: &str| -> Option<G::Input> {
                 // Game developer controls input parsing
                 G::parse_line(line)
+            },
+            |key: &KeyEvent| -> Option<G::Input> {
+                // ...and decides what single keys mean
+                G::map_key(key)
             }
         ).await
     }
+    
+    /// Same connection as `start_client` - the script stands in for the keyboard, JSON for the renderer
+    async fn start_headless(&self, addr: &str, name: String, options: JoinOptions, script: &ScriptOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let client = Self::client(name, options).connect_headless::<G::View, G::Input>(&ws_url(addr)).await?;
+        headless::run_script(client, script, |line: &str| G::parse_line(line)).await
+    }
+    
+    /// Bench clients decode the game's own views and pick moves with its bot
+    async fn bench(&self, options: &BenchOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let report = bench::run::<G>(options).await?;
+        println!("{}", report);
+        Ok(())
+    }
+    
+    /// Replays draw with the same renderer players saw - verification runs the game's own hooks
+    fn replay(&self, recording: &Recording, options: &ReplayOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if options.verify {
+            let events = recording::verify::<G>(recording)?;
+            println!("✅ Replayed {} events from seed {} - every state matches the recording", events, recording.header.seed);
+            return Ok(());
+        }
+        replay::play::<G, R>(recording, options)
+    }
 }
 
 /// Registry of auto-injected games - no factories!
@@ -129,7 +220,7 @@ impl GameRegistry {
     pub fn register_game<G, R>(&mut self)
     where
         G: WebSocketGame + Send + Sync + 'static,
-        R: GameRenderer<G::State> + 'static,
+        R: GameRenderer<G::View> + 'static,
     {
         let registration = GenericGameRegistration::<G, R>::new();
         let name = G::NAME.to_string(); // Get name from trait constant
@@ -141,55 +232,113 @@ impl GameRegistry {
         self.games.values().map(|r| r.metadata()).collect()
     }
 
+    /// Settings for hosting a game - unknown keys and bad values are turned away here
+    pub fn resolve_settings(&self, name: &str, settings: &Settings) -> Result<Settings, Box<dyn std::error::Error + Send + Sync>> {
+        match self.games.get(name) {
+            Some(registration) => Ok(registration.resolve_settings(settings)?),
+            None => Err(format!("Game '{}' not found", name).into()),
+        }
+    }
+
     /// Start a game - framework auto-injects hosting
-    pub async fn start_game(&self, name: &str, addr: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn start_game(&self, name: &str, addr: &str, options: HostOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match self.games.get(name) {
+            Some(registration) => registration.start_host(addr, options).await,
+            None => Err(format!("Game '{}' not found", name).into()),
+        }
+    }
+    
+    /// Create a room for a lobby - framework auto-injects fresh game state
+    pub fn create_room(&self, name: &str, room_id: String, options: &HostOptions) -> Result<Arc<dyn Room>, Box<dyn std::error::Error + Send + Sync>> {
         match self.games.get(name) {
-            Some(registration) => registration.start_host(addr).await,
+            Some(registration) => Ok(registration.create_room(room_id, options)),
             None => Err(format!("Game '{}' not found", name).into()),
         }
     }
     
     /// Join game - framework auto-injects client with renderer
-    pub async fn join_game(&self, name: &str, addr: &str, player_name: String) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn join_game(&self, name: &str, addr: &str, player_name: String, options: JoinOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         match self.games.get(name) {
-            Some(registration) => registration.start_client(addr, player_name).await,
+            Some(registration) => registration.start_client(addr, player_name, options).await,
             None => Err(format!("Game '{}' not found", name).into()),
         }
     }
 
-    /// Auto-detect and join - framework handles everything
-    pub async fn auto_detect_and_join(&self, addr: &str, player_name: String) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let ws_url = if addr.starts_with("ws://") || addr.starts_with("wss://") {
-            addr.to_string()
-        } else {
-            format!("ws://{}", addr)
-        };
+    /// Auto-detect and join (or watch) - framework handles everything
+    pub async fn auto_detect_and_join(&self, addr: &str, player_name: String, options: JoinOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let ws_url = ws_url(addr);
         
         println!("🔍 Detecting game type at {}...", ws_url);
         
         let (ws_stream, _) = tokio_tungstenite::connect_async(&ws_url).await
             .map_err(|e| format!("Failed to connect to {}: {}", ws_url, e))?;
             
-        let (mut _ws_sender, mut ws_receiver) = ws_stream.split();
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        
+        // A bare hello - we don't know which game we want yet
+        let welcome = protocol::client_handshake(&mut ws_sender, &mut ws_receiver, Hello::new(None)).await?;
         
-        if let Some(msg_result) = ws_receiver.next().await {
-            match msg_result {
-                Ok(tokio_tungstenite::tungstenite::protocol::Message::Text(text)) => {
-                    if let Ok(detection_msg) = serde_json::from_str::<DetectionMessage>(&text) {
-                        if let DetectionMessage::GameMetadata { name, version, description } = detection_msg {
-                            println!("✨ Detected game: {} v{} - {}", name, version, description);
-                            
-                            // Auto-inject client for detected game
-                            return self.join_game(&name, addr, player_name).await;
-                        }
-                    }
-                }
-                Ok(_) => {},
-                Err(e) => return Err(format!("WebSocket error: {}", e).into()),
+        match welcome.server {
+            ServerInfo::Game(game) => {
+                println!("✨ Detected game: {} v{} - {}", game.name, game.version, game.description);
+                
+                // Auto-inject client for detected game
+                self.join_game(&game.name, addr, player_name, options).await
+            }
+            ServerInfo::Lobby { name, games, rooms, .. } => {
+                println!("✨ Detected lobby: {} ({} rooms)", name, rooms.len());
+                
+                // Let the player pick or create a room, then join it like any game
+                let games = games.into_iter().map(|g| g.name).collect();
+                let room = choose_room(&mut ws_sender, &mut ws_receiver, games, rooms).await?;
+                self.join_game(&room.game, addr, player_name, JoinOptions { room: Some(room.id), ..options }).await
             }
         }
+    }
+
+    /// Auto-detect and run a script without a terminal - lobbies need `options.room`, there's no picker
+    pub async fn headless_join(&self, addr: &str, player_name: String, options: JoinOptions, script: &ScriptOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let ws_url = ws_url(addr);
+        let (ws_stream, _) = tokio_tungstenite::connect_async(&ws_url).await
+            .map_err(|e| format!("Failed to connect to {}: {}", ws_url, e))?;
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        let welcome = protocol::client_handshake(&mut ws_sender, &mut ws_receiver, Hello::new(None)).await?;
+        // Only asked to find the game - don't hold a second connection open for the whole run
+        let _ = ws_sender.close().await;
+        
+        let game = match (welcome.server, &options.room) {
+            (ServerInfo::Game(game), _) => game.name,
+            (ServerInfo::Lobby { rooms, .. }, Some(room_id)) => match rooms.into_iter().find(|r| &r.id == room_id) {
+                Some(room) => room.game,
+                None => return Err(format!("The lobby has no room '{}'", room_id).into()),
+            },
+            (ServerInfo::Lobby { rooms, .. }, None) => {
+                let ids: Vec<String> = rooms.into_iter().map(|r| r.id).collect();
+                return Err(format!("Server is a lobby - pass --room (open rooms: {})", if ids.is_empty() { "none".to_string() } else { ids.join(", ") }).into());
+            }
+        };
         
-        Err("Failed to detect game type from server".into())
+        match self.games.get(&game) {
+            Some(registration) => registration.start_headless(addr, player_name, options, script).await,
+            None => Err(format!("Game '{}' not found", game).into()),
+        }
+    }
+
+    /// Benchmark a game's server - framework auto-injects the clients
+    pub async fn bench(&self, name: &str, options: &BenchOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match self.games.get(name) {
+            Some(registration) => registration.bench(options).await,
+            None => Err(format!("Game '{}' not found", name).into()),
+        }
+    }
+
+    /// Replay a recorded session - the recording says which game it was
+    pub fn replay(&self, path: &Path, options: &ReplayOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let recording = Recording::load(path)?;
+        match self.games.get(&recording.header.game) {
+            Some(registration) => registration.replay(&recording, options),
+            None => Err(format!("Recording is of '{}', which isn't a game this build knows", recording.header.game).into()),
+        }
     }
 
     /// Check if a game exists
@@ -198,6 +347,15 @@ impl GameRegistry {
     }
 }
 
+/// Bare addresses get the `ws://` scheme
+fn ws_url(addr: &str) -> String {
+    if addr.starts_with("ws://") || addr.starts_with("wss://") {
+        addr.to_string()
+    } else {
+        format!("ws://{}", addr)
+    }
+}
+
 impl Default for GameRegistry {
     fn default() -> Self {
         Self::new()
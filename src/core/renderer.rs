@@ -7,5 +7,6 @@ pub trait GameRenderer<State>: Send + Sync {
     fn new(player_name: String) -> Self where Self: Sized;
     
     /// Render game state using terminal context - no more manual \r handling!
+    /// `ctx` is the game viewport below the status bar - don't clear the screen, the framework already did
     fn render(&self, state: &State, ctx: &mut TerminalContext);
 }
@@ -0,0 +1,1036 @@
+/// Game rooms - one game instance with its own state and sessions
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use tokio::sync::{Mutex, RwLock, broadcast};
+use tokio::time::{Duration, Instant, MissedTickBehavior};
+use uuid::Uuid;
+
+use crate::core::bot::Bot;
+use crate::core::chat::{self, ChatLog, RateLimiter};
+use crate::core::context::{self, GameContext, GameRng, Outgoing, Player, PlayerId, PlayerRole, Recipient, Settings};
+use crate::core::game::WebSocketGame;
+use crate::core::recording::{Entry, Event, Header, Recorder};
+use crate::core::save::{SavedRoom, SavedSeat, SavedTimer};
+use crate::core::settings;
+use crate::core::timers::{TimerRequest, Timers};
+use crate::core::protocol::{self, Capability, ChatMessage, ClientMessage, ErrorCode, GameMetadata, ProtocolError, Role, ServerMessage};
+use crate::core::websocket::PlayerSession;
+use crate::core::websocket_host::HostOptions;
+
+/// How often rooms without a tick rate check for due timers
+pub(crate) const TIMER_RESOLUTION: Duration = Duration::from_millis(100);
+
+/// Chat line the host types to save the game
+const SAVE_COMMAND: &str = "/save";
+
+/// Chat line the host types to seat a bot
+const BOT_COMMAND: &str = "/bot";
+
+/// How long a bot looks at a new view before moving - instant replies are hard to follow
+const BOT_THINK_TIME: Duration = Duration::from_millis(700);
+
+/// Public summary of a room - what lobby listings show
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomInfo {
+    pub id: String,
+    pub game: String,
+    pub players: usize,
+    pub max_players: usize,
+    #[serde(default)]
+    pub spectators: usize,
+    /// Seats played by bots - counted in `players` too
+    #[serde(default)]
+    pub bots: usize,
+}
+
+/// Type-erased room so a lobby can host any registered game side by side
+#[async_trait]
+pub trait Room: Send + Sync {
+    /// Summary for lobby listings
+    async fn info(&self) -> RoomInfo;
+
+    /// Game name, version and description - checked against the client's `Hello`
+    fn metadata(&self) -> GameMetadata;
+
+    /// Handle a raw client frame for this session - `capabilities` are the ones its handshake negotiated
+    /// Returns false once the session asked to leave the room
+    async fn handle_text(&self, session_id: &str, sender: &broadcast::Sender<String>, capabilities: &[Capability], text: &str) -> bool;
+
+    /// Session left on purpose - runs the game's leave hook right away
+    async fn leave(&self, session_id: &str);
+
+    /// Session's connection dropped - holds the seat for the reconnect grace period,
+    /// then runs the leave hook unless the player resumed. Resolves once that is decided.
+    async fn disconnect(&self, session_id: &str);
+
+    /// Number of seated sessions, including ones waiting to reconnect
+    async fn session_count(&self) -> usize;
+
+    /// Number of sessions watching without a seat
+    async fn spectator_count(&self) -> usize;
+
+    /// Number of seats played by bots
+    async fn bot_count(&self) -> usize;
+
+    /// Humans in the room, seated or watching - rooms with only bots left count as empty
+    async fn human_count(&self) -> usize;
+
+    /// Seat a bot in the next free seat - returns its name
+    async fn add_bot(&self) -> Result<String, ProtocolError>;
+}
+
+/// A bot in one of the room's seats
+struct BotSeat<G: WebSocketGame> {
+    session_id: String,
+    bot: Box<dyn Bot<G>>,
+    /// The bot's own dice - seeded from the room so bot games replay too
+    rng: GameRng,
+    /// Last view it was shown, serialized - a change restarts the think time
+    last_view: Option<String>,
+    changed_at: Instant,
+}
+
+/// A single game instance - the typed heart of every server
+pub struct GameRoom<G: WebSocketGame> {
+    id: String,
+    metadata: GameMetadata,
+    options: HostOptions,
+    /// Seed the room's RNG started from - logged so the game can be replayed
+    seed: u64,
+    state: RwLock<G::State>,
+    rng: Mutex<GameRng>,
+    /// When the room opened - the game's clock counts from here
+    started: Instant,
+    next_id: AtomicU64,
+    sessions: RwLock<HashMap<String, PlayerSession>>,
+    /// Pending one-shot timers by key
+    timers: Mutex<HashMap<String, Instant>>,
+    chat: Mutex<ChatLog>,
+    /// Session log for `lanterm replay` - only when the host asked for one
+    recorder: Option<Mutex<Recorder>>,
+    bots: Mutex<Vec<BotSeat<G>>>,
+}
+
+impl<G: WebSocketGame> GameRoom<G> {
+    /// Set up a fresh game - seeded from `options.seed`, or a random seed that gets logged
+    pub fn new(id: String, metadata: GameMetadata, options: HostOptions) -> Self {
+        let seed = options.seed.unwrap_or_else(context::random_seed);
+        println!("🎲 Room {} seed: {}", id, seed);
+
+        let mut rng = context::seeded_rng(seed);
+        let settings = settings::with_defaults(G::SETTINGS, &options.settings);
+        let mut ctx = GameContext::new(&mut rng, Duration::ZERO).with_settings(settings.clone());
+        let initial_state = G::new_game(&mut ctx);
+        // Nobody is around to read messages yet - only the timers matter
+        let (requests, _) = ctx.into_parts();
+        let mut timers = HashMap::new();
+        Self::schedule(&mut timers, requests);
+        let recorder = options.record.as_deref().and_then(|path| Self::start_recording(path, &id, seed, &settings, &initial_state, false));
+
+        Self {
+            id,
+            metadata,
+            options,
+            seed,
+            state: RwLock::new(initial_state),
+            rng: Mutex::new(rng),
+            started: Instant::now(),
+            next_id: AtomicU64::new(1),
+            sessions: RwLock::new(HashMap::new()),
+            timers: Mutex::new(timers),
+            chat: Mutex::new(ChatLog::new()),
+            recorder: recorder.map(Mutex::new),
+            bots: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Pick a saved game back up - every seat is held until its player rejoins by name or token
+    pub fn restore(id: String, metadata: GameMetadata, options: HostOptions, saved: SavedRoom) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if saved.game != G::NAME {
+            return Err(format!("That save is a {} game, not {}", saved.game, G::NAME).into());
+        }
+        if saved.version != G::VERSION {
+            println!("⚠️  Saved with {} v{}, resuming on v{}", saved.game, saved.version, G::VERSION);
+        }
+        let state: G::State = serde_json::from_value(saved.state.clone())
+            .map_err(|e| format!("Saved state doesn't fit this version of {}: {}", G::NAME, e))?;
+
+        let now = Instant::now();
+        let timers = saved.timers.iter()
+            .map(|t| (t.key.clone(), now + Duration::from_millis(t.in_ms)))
+            .collect();
+
+        // Nobody is connected yet - held seats wait without a grace timer
+        let sessions = saved.seats.iter()
+            .map(|seat| {
+                let session = PlayerSession {
+                    id: seat.id,
+                    name: seat.name.clone(),
+                    role: Role::Player,
+                    seat: Some(seat.seat),
+                    sender: broadcast::channel(1).0,
+                    resume_token: seat.resume_token.clone(),
+                    connected: seat.bot,
+                    disconnect_epoch: 0,
+                    chat_limiter: RateLimiter::default(),
+                    chat: false,
+                    bot: seat.bot,
+                    restored: !seat.bot,
+                };
+                (format!("saved-{}", seat.id.0), session)
+            })
+            .collect();
+
+        println!("💾 Resumed room {} from a save made {}", id, saved.saved_at.format("%Y-%m-%d %H:%M"));
+        let waiting = saved.player_names();
+        if !waiting.is_empty() {
+            println!("   Holding seats for {} - rejoin with the same name", waiting.join(", "));
+        }
+        println!("🎲 Room {} seed: {} (resumed with {})", id, saved.seed, saved.rng_seed);
+
+        let recorder = options.record.as_deref().and_then(|path| Self::start_recording(path, &id, saved.rng_seed, &Settings::new(), &state, true));
+        let bots = saved.seats.iter()
+            .filter(|seat| seat.bot)
+            .filter_map(|seat| Some(Self::bot_seat(format!("saved-{}", seat.id.0), saved.seed, seat.id, G::bot()?)))
+            .collect();
+
+        Ok(Self {
+            id,
+            metadata,
+            options,
+            seed: saved.seed,
+            state: RwLock::new(state),
+            rng: Mutex::new(context::seeded_rng(saved.rng_seed)),
+            started: Instant::now(),
+            next_id: AtomicU64::new(saved.next_id),
+            sessions: RwLock::new(sessions),
+            timers: Mutex::new(timers),
+            chat: Mutex::new(ChatLog::new()),
+            recorder: recorder.map(Mutex::new),
+            bots: Mutex::new(bots),
+        })
+    }
+
+    /// Open the session log with the starting state - a room that can't record still plays
+    fn start_recording(path: &Path, room: &str, seed: u64, settings: &Settings, state: &G::State, resumed: bool) -> Option<Recorder> {
+        let header = Header {
+            game: G::NAME.to_string(),
+            version: G::VERSION.to_string(),
+            room: room.to_string(),
+            seed,
+            settings: settings.clone(),
+            started_at: chrono::Utc::now(),
+            resumed,
+            state: serde_json::to_value(state).ok()?,
+        };
+        match Recorder::create(path, &header) {
+            Ok(recorder) => {
+                println!("📼 Recording room {} to {}", room, recorder.path().display());
+                Some(recorder)
+            }
+            Err(e) => {
+                eprintln!("⚠️  Can't record room {} to {}: {}", room, path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Drive ticks and timers for this room - the clock stops once the room is dropped
+    pub fn start_clock(room: &Arc<Self>) {
+        let room = Arc::downgrade(room);
+        let period = if G::TICK_RATE > 0 {
+            Duration::from_secs(1) / G::TICK_RATE
+        } else {
+            TIMER_RESOLUTION
+        };
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                let Some(room) = room.upgrade() else { break };
+                room.on_clock().await;
+            }
+        });
+    }
+
+    /// Save on SIGUSR1, and once more on Ctrl-C right before the host exits
+    pub fn save_on_signals(room: &Arc<Self>, path: PathBuf) {
+        #[cfg(unix)]
+        {
+            let room = Arc::clone(room);
+            let path = path.clone();
+            tokio::spawn(async move {
+                use tokio::signal::unix::{signal, SignalKind};
+                let Ok(mut usr1) = signal(SignalKind::user_defined1()) else { return };
+                while usr1.recv().await.is_some() {
+                    if let Err(e) = room.save(&path).await {
+                        eprintln!("⚠️  Couldn't save room {}: {}", room.id, e);
+                    }
+                }
+            });
+        }
+
+        let room = Arc::clone(room);
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                if let Err(e) = room.save(&path).await {
+                    eprintln!("⚠️  Couldn't save room {}: {}", room.id, e);
+                }
+                std::process::exit(0);
+            }
+        });
+    }
+
+    /// Snapshot state, seats, timers and RNG together under the room's locks, then write them out
+    pub async fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let saved = {
+            let state = self.state.read().await;
+            let sessions = self.sessions.read().await;
+            let rng = self.rng.lock().await;
+            let timers = self.timers.lock().await;
+            let now = Instant::now();
+
+            let mut seats: Vec<SavedSeat> = sessions.values()
+                .filter_map(|s| s.seat.map(|seat| SavedSeat {
+                    id: s.id,
+                    name: s.name.clone(),
+                    seat,
+                    resume_token: s.resume_token.clone(),
+                    bot: s.bot,
+                }))
+                .collect();
+            seats.sort_by_key(|s| s.seat);
+
+            SavedRoom {
+                game: G::NAME.to_string(),
+                version: G::VERSION.to_string(),
+                room: self.id.clone(),
+                seed: self.seed,
+                // Drawn from a copy so saving doesn't change what the live game rolls next
+                rng_seed: rng.clone().gen(),
+                saved_at: chrono::Utc::now(),
+                next_id: self.next_id.load(Ordering::Relaxed),
+                seats,
+                timers: timers.iter()
+                    .map(|(key, deadline)| SavedTimer {
+                        key: key.clone(),
+                        in_ms: deadline.saturating_duration_since(now).as_millis() as u64,
+                    })
+                    .collect(),
+                state: serde_json::to_value(&*state)?,
+            }
+        };
+
+        saved.write(path)?;
+        println!("💾 Saved room {} to {} ({} seats)", self.id, path.display(), saved.seats.len());
+        Ok(())
+    }
+
+    /// One clock step - tick the game and fire due timers under a single state lock
+    async fn on_clock(&self) {
+        self.run_bots().await;
+
+        let due = self.take_due_timers().await;
+        if G::TICK_RATE == 0 && due.is_empty() {
+            return;
+        }
+
+        // Idle ticks stay out of the recording - a quiet 30 Hz room would otherwise log forever
+        let event = Event::Clock { tick: G::TICK_RATE > 0, timers: due.clone() };
+        let (changed, outbox) = self.run_hook_if(event, |changed| *changed, |state, ctx| {
+            let mut changed = false;
+            if G::TICK_RATE > 0 {
+                changed |= G::tick(state, ctx);
+            }
+            for key in due {
+                G::on_timer(state, &key, ctx);
+                changed = true;
+            }
+            changed
+        }).await;
+
+        // Only send views when something actually moved
+        if changed {
+            self.broadcast_views().await;
+        }
+        self.deliver(outbox).await;
+    }
+
+    /// Seat a bot the way a player joins - it runs the join hook and shows up in every view
+    pub async fn add_bot(&self) -> Result<String, ProtocolError> {
+        let Some(bot) = G::bot() else {
+            return Err(ProtocolError::new(ErrorCode::NotAllowed, format!("{} doesn't have a bot", G::NAME)));
+        };
+
+        let session_id = format!("bot-{}", Uuid::new_v4());
+        let player = {
+            let mut sessions = self.sessions.write().await;
+            let seated = sessions.values().filter(|s| s.role == Role::Player).count();
+            if seated >= G::MAX_PLAYERS {
+                return Err(ProtocolError::new(ErrorCode::RoomFull, format!("All {} seats are taken", G::MAX_PLAYERS)));
+            }
+            let session = PlayerSession {
+                id: self.next_player_id(),
+                name: Self::unique_name(&sessions, bot.name()),
+                role: Role::Player,
+                seat: Some(Self::free_seat(&sessions)),
+                // Nobody listens - views sent here are simply dropped
+                sender: broadcast::channel(1).0,
+                resume_token: Uuid::new_v4().to_string(),
+                connected: true,
+                disconnect_epoch: 0,
+                chat_limiter: RateLimiter::default(),
+                chat: false,
+                bot: true,
+                restored: false,
+            };
+            sessions.insert(session_id.clone(), session);
+            Self::player_of(&sessions, &sessions[&session_id])
+        };
+
+        println!("🤖 Bot '{}' ({}) joined room {}", player.name, player.id, self.id);
+        self.bots.lock().await.push(Self::bot_seat(session_id, self.seed, player.id, bot));
+
+        let name = player.name.clone();
+        let ((), outbox) = self.run_hook(Event::Join { player }, G::on_player_join).await;
+        self.broadcast_views().await;
+        self.deliver(outbox).await;
+        Ok(name)
+    }
+
+    fn bot_seat(session_id: String, seed: u64, id: PlayerId, bot: Box<dyn Bot<G>>) -> BotSeat<G> {
+        BotSeat {
+            session_id,
+            bot,
+            rng: context::seeded_rng(seed ^ id.0),
+            last_view: None,
+            changed_at: Instant::now(),
+        }
+    }
+
+    /// Let every bot that has looked at its view long enough make a move - through `input`, like anyone
+    async fn run_bots(&self) {
+        let moves = {
+            let mut bots = self.bots.lock().await;
+            if bots.is_empty() {
+                return;
+            }
+            let state = self.state.read().await;
+            let sessions = self.sessions.read().await;
+            let now = Instant::now();
+
+            let mut moves = Vec::new();
+            for seat in bots.iter_mut() {
+                let Some(session) = sessions.get(&seat.session_id) else { continue };
+                let player = Self::player_of(&sessions, session);
+                let view = G::view(&state, &player);
+
+                let seen = serde_json::to_string(&view).ok();
+                if seen != seat.last_view {
+                    seat.last_view = seen;
+                    seat.changed_at = now;
+                    continue;
+                }
+                if now.duration_since(seat.changed_at) < BOT_THINK_TIME {
+                    continue;
+                }
+                if let Some(input) = seat.bot.choose(&view, &player, &mut seat.rng) {
+                    // Think again before retrying, even if the move didn't change anything
+                    seat.changed_at = now;
+                    moves.push((seat.session_id.clone(), input));
+                }
+            }
+            moves
+        };
+
+        for (session_id, input) in moves {
+            let _ = self.input(&session_id, &input).await;
+        }
+    }
+
+    /// Remove and return timers whose deadline passed, earliest first
+    async fn take_due_timers(&self) -> Vec<String> {
+        let now = Instant::now();
+        let mut pending = self.timers.lock().await;
+
+        let mut due: Vec<(Instant, String)> = pending.iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(key, deadline)| (*deadline, key.clone()))
+            .collect();
+        due.sort();
+
+        for (_, key) in &due {
+            pending.remove(key);
+        }
+        due.into_iter().map(|(_, key)| key).collect()
+    }
+
+    /// Run a game hook for `event` under the state lock with the room's RNG, then apply the timers it armed
+    /// Hands back the messages it queued - deliver them once everyone has the new view
+    async fn run_hook<T>(&self, event: Event, hook: impl FnOnce(&mut G::State, &mut GameContext) -> T) -> (T, Vec<Outgoing>) {
+        self.run_hook_if(event, |_| true, hook).await
+    }
+
+    /// `run_hook`, but only recorded when `keep` says the hook did something or it armed timers or queued messages
+    async fn run_hook_if<T>(
+        &self,
+        event: Event,
+        keep: impl FnOnce(&T) -> bool,
+        hook: impl FnOnce(&mut G::State, &mut GameContext) -> T,
+    ) -> (T, Vec<Outgoing>) {
+        let (result, timers, outbox) = {
+            let mut state = self.state.write().await;
+            let mut rng = self.rng.lock().await;
+            // Whole milliseconds, so a recording replays the exact clock the game saw
+            let at_ms = self.started.elapsed().as_millis() as u64;
+            let now = Duration::from_millis(at_ms);
+            let mut ctx = match event.player() {
+                Some(player) => GameContext::for_player(&mut rng, now, player.clone()),
+                None => GameContext::new(&mut rng, now),
+            };
+            let result = hook(&mut state, &mut ctx);
+            let (timers, outbox) = ctx.into_parts();
+
+            // Logged while the state is still locked, so entries land in the order they happened
+            let record = keep(&result) || !timers.is_empty() || !outbox.is_empty();
+            if let Some(recorder) = self.recorder.as_ref().filter(|_| record) {
+                if let Ok(recorded) = serde_json::to_value(&*state) {
+                    recorder.lock().await.record(&Entry { at_ms, event, state: recorded });
+                }
+            }
+            (result, timers, outbox)
+        };
+        if !timers.is_empty() {
+            Self::schedule(&mut *self.timers.lock().await, timers);
+        }
+        (result, outbox)
+    }
+
+    /// How the game sees a session - the seated human with the lowest seat hosts
+    fn player_of(sessions: &HashMap<String, PlayerSession>, session: &PlayerSession) -> Player {
+        let host_seat = sessions.values().filter(|s| !s.bot).filter_map(|s| s.seat).min();
+        let role = match session.seat {
+            Some(seat) if Some(seat) == host_seat => PlayerRole::Host,
+            Some(_) => PlayerRole::Player,
+            None => PlayerRole::Spectator,
+        };
+        Player { id: session.id, name: session.name.clone(), seat: session.seat, role }
+    }
+
+    /// Keep display names unique - a second "Player" shows up as "Player (2)"
+    fn unique_name(sessions: &HashMap<String, PlayerSession>, name: &str) -> String {
+        let taken = |candidate: &str| sessions.values().any(|s| s.name == candidate);
+        if !taken(name) {
+            return name.to_string();
+        }
+        (2..).map(|n| format!("{} ({})", name, n))
+            .find(|candidate| !taken(candidate))
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Lowest seat nobody holds - seats free up when players leave
+    fn free_seat(sessions: &HashMap<String, PlayerSession>) -> usize {
+        (0..).find(|seat| !sessions.values().any(|s| s.seat == Some(*seat))).unwrap_or(0)
+    }
+
+    fn next_player_id(&self) -> PlayerId {
+        PlayerId(self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Apply whatever a hook asked for - schedules and cancels in order
+    fn schedule(pending: &mut HashMap<String, Instant>, timers: Timers) {
+        for request in timers.into_requests() {
+            match request {
+                TimerRequest::Schedule { key, after } => {
+                    pending.insert(key, Instant::now() + after);
+                }
+                TimerRequest::Cancel { key } => {
+                    pending.remove(&key);
+                }
+            }
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Seed the room's RNG started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Register a session and run the game's join hook under the state lock
+    /// A valid resume token reclaims a held seat instead. `chat` is whether the client negotiated chat frames.
+    pub async fn join(&self, session_id: &str, name: String, resume_token: Option<String>, sender: broadcast::Sender<String>, chat: bool) {
+        if let Some(token) = resume_token {
+            if self.resume(session_id, &token, sender.clone(), chat).await {
+                return;
+            }
+        }
+        // Seats restored from a save go back to their name - whoever held the token may be long gone
+        if let Some(token) = self.held_seat(&name).await {
+            if self.resume(session_id, &token, sender.clone(), chat).await {
+                return;
+            }
+        }
+
+        let resume_token = Uuid::new_v4().to_string();
+        let player = {
+            let mut sessions = self.sessions.write().await;
+            if sessions.contains_key(session_id) {
+                // Already joined on this connection
+                return;
+            }
+            let seated = sessions.values().filter(|s| s.role == Role::Player).count();
+            if seated >= G::MAX_PLAYERS {
+                let message = format!("All {} seats are taken - use `lanterm watch` to spectate", G::MAX_PLAYERS);
+                let _ = sender.send(protocol::error_frame(ErrorCode::RoomFull, message));
+                return;
+            }
+            let session = PlayerSession {
+                id: self.next_player_id(),
+                name: Self::unique_name(&sessions, &name),
+                role: Role::Player,
+                seat: Some(Self::free_seat(&sessions)),
+                sender,
+                resume_token: resume_token.clone(),
+                connected: true,
+                disconnect_epoch: 0,
+                chat_limiter: RateLimiter::default(),
+                chat,
+                bot: false,
+                restored: false,
+            };
+            sessions.insert(session_id.to_string(), session);
+            Self::player_of(&sessions, &sessions[session_id])
+        };
+
+        println!("Player '{}' ({}) joined room {}", player.name, player.id, self.id);
+
+        let joined = ServerMessage::Joined { resume_token, player_id: player.id, name: player.name.clone() };
+        let ((), outbox) = self.run_hook(Event::Join { player }, G::on_player_join).await;
+
+        self.send(session_id, &joined).await;
+        self.send_chat_history(session_id).await;
+        self.broadcast_views().await;
+        self.deliver(outbox).await;
+    }
+
+    /// Register a watcher - no seat, no game hooks, just the spectator view
+    pub async fn spectate(&self, session_id: &str, name: String, sender: broadcast::Sender<String>, chat: bool) {
+        let name = {
+            let mut sessions = self.sessions.write().await;
+            if sessions.contains_key(session_id) {
+                return;
+            }
+            let name = Self::unique_name(&sessions, &name);
+            sessions.insert(session_id.to_string(), PlayerSession {
+                id: self.next_player_id(),
+                name: name.clone(),
+                role: Role::Spectator,
+                seat: None,
+                sender,
+                resume_token: Uuid::new_v4().to_string(),
+                connected: true,
+                disconnect_epoch: 0,
+                chat_limiter: RateLimiter::default(),
+                chat,
+                bot: false,
+                restored: false,
+            });
+            name
+        };
+
+        println!("Spectator '{}' is watching room {}", name, self.id);
+
+        self.send(session_id, &ServerMessage::Spectating).await;
+        self.send_chat_history(session_id).await;
+        {
+            // State before sessions, like `broadcast_views` - the other order deadlocks behind a queued writer
+            let state = self.state.read().await;
+            let sessions = self.sessions.read().await;
+            if let Some(json) = sessions.get(session_id).and_then(|session| Self::view_message(&state, &sessions, session)) {
+                let _ = sessions[session_id].sender.send(json);
+            }
+        }
+        self.broadcast_message(format!("👀 {} is watching.", name)).await;
+    }
+
+    /// Resume token of a restored seat still waiting for `name`
+    /// Seats dropped mid-game need their token - otherwise anyone could take them by name
+    async fn held_seat(&self, name: &str) -> Option<String> {
+        self.sessions.read().await.values()
+            .find(|s| s.restored && !s.connected && s.name == name)
+            .map(|s| s.resume_token.clone())
+    }
+
+    /// Move a held seat onto a new connection - the game never notices the drop
+    async fn resume(&self, session_id: &str, token: &str, sender: broadcast::Sender<String>, chat: bool) -> bool {
+        let (name, player_id) = {
+            let mut sessions = self.sessions.write().await;
+            let Some(old_id) = sessions.iter()
+                .find(|(_, s)| s.resume_token == token)
+                .map(|(id, _)| id.clone()) else {
+                return false;
+            };
+
+            let Some(mut session) = sessions.remove(&old_id) else {
+                return false;
+            };
+            session.sender = sender;
+            session.connected = true;
+            session.chat = chat;
+            session.restored = false;
+            let resumed = (session.name.clone(), session.id);
+            sessions.insert(session_id.to_string(), session);
+            resumed
+        };
+
+        println!("Player '{}' resumed in room {}", name, self.id);
+
+        self.send(session_id, &ServerMessage::Joined { resume_token: token.to_string(), player_id, name: name.clone() }).await;
+        self.send_chat_history(session_id).await;
+        self.broadcast_views().await;
+        self.broadcast_message(format!("🔌 {} reconnected.", name)).await;
+        true
+    }
+
+    /// Apply one player's input and tell everyone about it
+    /// Sessions that aren't seated get an error back instead
+    pub async fn input(&self, session_id: &str, input: &G::Input) -> Result<(), ProtocolError> {
+        let player = {
+            let sessions = self.sessions.read().await;
+            match sessions.get(session_id) {
+                Some(session) if session.role == Role::Player => Self::player_of(&sessions, session),
+                Some(_) => return Err(ProtocolError::new(ErrorCode::Spectating, "Spectators can't play - join the game to take a seat")),
+                None => return Err(ProtocolError::new(ErrorCode::NotJoined, "Join the game before sending input")),
+            }
+        };
+
+        let event = Event::Input { player, input: serde_json::to_value(input).unwrap_or_default() };
+        let ((), outbox) = self.run_hook(event, |state, ctx| G::handle_input(input, state, ctx)).await;
+
+        // Send every player their own view of the updated state, then whatever the game said
+        self.broadcast_views().await;
+        self.deliver(outbox).await;
+        Ok(())
+    }
+
+    /// Relay a chat line to the room and remember it - players and spectators alike
+    pub async fn chat(&self, session_id: &str, text: &str) -> Result<(), ProtocolError> {
+        if text.trim() == SAVE_COMMAND {
+            return self.save_command(session_id).await;
+        }
+        if text.trim() == BOT_COMMAND {
+            return self.bot_command(session_id).await;
+        }
+
+        let message = {
+            let mut sessions = self.sessions.write().await;
+            let Some(session) = sessions.get_mut(session_id) else {
+                return Err(ProtocolError::new(ErrorCode::NotJoined, "Join the game before chatting"));
+            };
+            let Some(text) = chat::clean(text) else {
+                return Ok(());
+            };
+            if !session.chat_limiter.allow(Instant::now()) {
+                return Err(ProtocolError::new(ErrorCode::RateLimited, "Slow down - you're chatting too fast"));
+            }
+            ChatMessage {
+                from: session.name.clone(),
+                role: session.role,
+                text,
+                sent_at: chrono::Utc::now(),
+            }
+        };
+
+        self.chat.lock().await.push(message.clone());
+
+        if let Ok(json) = serde_json::to_string(&ServerMessage::<G::View>::Chat(message)) {
+            for session in self.sessions.read().await.values().filter(|s| s.connected && s.chat) {
+                let _ = session.sender.send(json.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Name of the session if it's the room's host - chat commands are theirs alone
+    async fn host_name(&self, session_id: &str, action: &str) -> Result<String, ProtocolError> {
+        let sessions = self.sessions.read().await;
+        let Some(session) = sessions.get(session_id) else {
+            return Err(ProtocolError::new(ErrorCode::NotJoined, format!("Join the game before you {}", action)));
+        };
+        if Self::player_of(&sessions, session).role != PlayerRole::Host {
+            return Err(ProtocolError::new(ErrorCode::NotAllowed, format!("Only the host can {}", action)));
+        }
+        Ok(session.name.clone())
+    }
+
+    /// The host typed `/bot` - fill the next empty seat with a bot
+    async fn bot_command(&self, session_id: &str) -> Result<(), ProtocolError> {
+        let host = self.host_name(session_id, "add bots").await?;
+        let bot = self.add_bot().await?;
+        self.broadcast_message(format!("🤖 {} seated {}.", host, bot)).await;
+        Ok(())
+    }
+
+    /// The host typed `/save` - write the game to the host's save file
+    async fn save_command(&self, session_id: &str) -> Result<(), ProtocolError> {
+        let name = self.host_name(session_id, "save the game").await?;
+        let Some(path) = self.options.save_path() else {
+            return Err(ProtocolError::new(ErrorCode::NotAllowed, "Saving is off - start the server with --save FILE"));
+        };
+
+        match self.save(path).await {
+            Ok(()) => {
+                self.broadcast_message(format!("💾 {} saved the game.", name)).await;
+                Ok(())
+            }
+            Err(e) => Err(ProtocolError::new(ErrorCode::NotAllowed, format!("Couldn't save: {}", e))),
+        }
+    }
+
+    /// Drop the session and let the game skip turns or forfeit the player
+    pub async fn leave(&self, session_id: &str) {
+        let (session, player) = {
+            let mut sessions = self.sessions.write().await;
+            let Some(player) = sessions.get(session_id).map(|s| Self::player_of(&sessions, s)) else {
+                return;
+            };
+            let Some(session) = sessions.remove(session_id) else {
+                return;
+            };
+            (session, player)
+        };
+
+        if session.role == Role::Spectator {
+            println!("Spectator '{}' stopped watching room {}", session.name, self.id);
+            return;
+        }
+
+        println!("Player '{}' left room {}", session.name, self.id);
+
+        let ((), outbox) = self.run_hook(Event::Leave { player }, G::on_player_leave).await;
+
+        self.broadcast_views().await;
+        self.deliver(outbox).await;
+    }
+
+    /// Hold a dropped player's seat, then give it up if they don't come back in time
+    pub async fn disconnect(&self, session_id: &str) {
+        let (name, token, epoch) = {
+            let mut sessions = self.sessions.write().await;
+            let Some(session) = sessions.get_mut(session_id) else {
+                return;
+            };
+            if session.role == Role::Spectator {
+                // Nothing to hold - watchers just spectate again
+                drop(sessions);
+                self.leave(session_id).await;
+                return;
+            }
+            session.connected = false;
+            session.disconnect_epoch += 1;
+            (session.name.clone(), session.resume_token.clone(), session.disconnect_epoch)
+        };
+
+        let grace = self.options.reconnect_grace;
+        if grace.is_zero() {
+            self.leave(session_id).await;
+            return;
+        }
+
+        println!("Player '{}' dropped from room {}, holding seat for {:?}", name, self.id, grace);
+        self.broadcast_message(format!("🔌 {} lost connection - holding their seat for {}s.", name, grace.as_secs())).await;
+
+        tokio::time::sleep(grace).await;
+
+        // Resumed sessions live under a new id, and a later drop bumps the epoch
+        let expired = self.sessions.read().await.iter()
+            .find(|(_, s)| s.resume_token == token && !s.connected && s.disconnect_epoch == epoch)
+            .map(|(id, _)| id.clone());
+
+        if let Some(expired_id) = expired {
+            self.leave(&expired_id).await;
+        }
+    }
+
+    /// Serialize a state update holding this session's own view
+    fn view_message(state: &G::State, sessions: &HashMap<String, PlayerSession>, session: &PlayerSession) -> Option<String> {
+        let view = match session.role {
+            Role::Player => G::view(state, &Self::player_of(sessions, session)),
+            Role::Spectator => G::spectator_view(state),
+        };
+        let msg = ServerMessage::StateUpdate(view);
+        serde_json::to_string(&msg).ok()
+    }
+
+    /// Project the current state once per session - nobody gets the raw state
+    async fn broadcast_views(&self) {
+        let state = self.state.read().await;
+        let sessions = self.sessions.read().await;
+        for session in sessions.values().filter(|s| s.connected) {
+            if let Some(json) = Self::view_message(&state, &sessions, session) {
+                let _ = session.sender.send(json);
+            }
+        }
+    }
+
+    /// Send a notification to every session in the room
+    async fn broadcast_message(&self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+        let msg = ServerMessage::<G::View>::Message(text);
+        if let Ok(json) = serde_json::to_string(&msg) {
+            for session in self.sessions.read().await.values().filter(|s| s.connected) {
+                let _ = session.sender.send(json.clone());
+            }
+        }
+    }
+
+    /// Hand the messages a hook queued to their recipients
+    async fn deliver(&self, outbox: Vec<Outgoing>) {
+        if outbox.is_empty() {
+            return;
+        }
+        let sessions = self.sessions.read().await;
+        for message in outbox {
+            let Ok(json) = serde_json::to_string(&ServerMessage::<G::View>::Message(message.text)) else {
+                continue;
+            };
+            for session in sessions.values().filter(|s| s.connected) {
+                let wanted = match message.to {
+                    Recipient::Player(id) => session.id == id,
+                    Recipient::Players => session.role == Role::Player,
+                    Recipient::Spectators => session.role == Role::Spectator,
+                    Recipient::Everyone => true,
+                };
+                if wanted {
+                    let _ = session.sender.send(json.clone());
+                }
+            }
+        }
+    }
+
+    /// Catch a newcomer up on the conversation - if their client does chat
+    async fn send_chat_history(&self, session_id: &str) {
+        if !self.sessions.read().await.get(session_id).is_some_and(|s| s.chat) {
+            return;
+        }
+        let history = self.chat.lock().await.history();
+        if !history.is_empty() {
+            self.send(session_id, &ServerMessage::ChatHistory(history)).await;
+        }
+    }
+
+    /// Send any protocol message to a single session
+    async fn send(&self, session_id: &str, msg: &ServerMessage<G::View>) {
+        if let Some(session) = self.sessions.read().await.get(session_id) {
+            if let Ok(json) = serde_json::to_string(msg) {
+                let _ = session.sender.send(json);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<G: WebSocketGame> Room for GameRoom<G> {
+    async fn info(&self) -> RoomInfo {
+        RoomInfo {
+            id: self.id.clone(),
+            game: G::NAME.to_string(),
+            players: self.session_count().await,
+            max_players: G::MAX_PLAYERS,
+            spectators: self.spectator_count().await,
+            bots: self.bot_count().await,
+        }
+    }
+
+    fn metadata(&self) -> GameMetadata {
+        self.metadata.clone()
+    }
+
+    async fn handle_text(&self, session_id: &str, sender: &broadcast::Sender<String>, capabilities: &[Capability], text: &str) -> bool {
+        let chat = capabilities.contains(&Capability::Chat);
+        let game_msg = match serde_json::from_str::<ClientMessage<G::Input>>(text) {
+            Ok(game_msg) => game_msg,
+            Err(e) => {
+                let _ = sender.send(protocol::error_frame(ErrorCode::MalformedFrame, format!("Could not read frame: {}", e)));
+                return true;
+            }
+        };
+
+        match game_msg {
+            ClientMessage::PlayerJoin { name, resume_token } => {
+                self.join(session_id, name, resume_token, sender.clone(), chat).await;
+            }
+
+            ClientMessage::Spectate { name } => {
+                self.spectate(session_id, name, sender.clone(), chat).await;
+            }
+
+            ClientMessage::PlayerInput(input) => {
+                if let Err(err) = self.input(session_id, &input).await {
+                    let _ = sender.send(protocol::error_frame(err.code, err.message));
+                }
+            }
+
+            ClientMessage::Chat(text) => {
+                if let Err(err) = self.chat(session_id, &text).await {
+                    let _ = sender.send(protocol::error_frame(err.code, err.message));
+                }
+            }
+
+            ClientMessage::PlayerLeave => {
+                return false;
+            }
+
+            ClientMessage::Hello(_) => {
+                let _ = sender.send(protocol::error_frame(ErrorCode::UnexpectedFrame, "Handshake already done"));
+            }
+
+            ClientMessage::ListRooms | ClientMessage::CreateRoom { .. } | ClientMessage::JoinRoom { .. } | ClientMessage::LeaveRoom => {
+                let _ = sender.send(protocol::error_frame(ErrorCode::NotALobby, "This server hosts a single game, not a lobby"));
+            }
+        }
+        true
+    }
+
+    async fn leave(&self, session_id: &str) {
+        GameRoom::leave(self, session_id).await;
+    }
+
+    async fn disconnect(&self, session_id: &str) {
+        GameRoom::disconnect(self, session_id).await;
+    }
+
+    async fn session_count(&self) -> usize {
+        self.sessions.read().await.values().filter(|s| s.role == Role::Player).count()
+    }
+
+    async fn spectator_count(&self) -> usize {
+        self.sessions.read().await.values().filter(|s| s.role == Role::Spectator).count()
+    }
+
+    async fn bot_count(&self) -> usize {
+        self.sessions.read().await.values().filter(|s| s.bot).count()
+    }
+
+    async fn human_count(&self) -> usize {
+        self.sessions.read().await.values().filter(|s| !s.bot).count()
+    }
+
+    async fn add_bot(&self) -> Result<String, ProtocolError> {
+        GameRoom::add_bot(self).await
+    }
+}
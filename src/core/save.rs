@@ -0,0 +1,67 @@
+/// Saved games - a room's state, seats and timers on disk so a match can pick up where it left off
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::core::context::PlayerId;
+
+/// Everything a room needs to come back - written by `save`, read by `host --resume`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedRoom {
+    pub game: String,
+    pub version: String,
+    pub room: String,
+    /// Seed the room originally started from - kept for the logs
+    pub seed: u64,
+    /// Seed for the RNG after resuming - drawn from the room's RNG at save time
+    pub rng_seed: u64,
+    pub saved_at: chrono::DateTime<chrono::Utc>,
+    /// Next player id to hand out - restored seats keep theirs
+    pub next_id: u64,
+    pub seats: Vec<SavedSeat>,
+    pub timers: Vec<SavedTimer>,
+    pub state: Value,
+}
+
+/// A seated player - held for them until they come back by name or token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSeat {
+    pub id: PlayerId,
+    pub name: String,
+    pub seat: usize,
+    pub resume_token: String,
+    /// Played by a server-side bot - it takes the seat straight back on resume
+    #[serde(default)]
+    pub bot: bool,
+}
+
+/// A pending timer and how long it had left
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedTimer {
+    pub key: String,
+    pub in_ms: u64,
+}
+
+impl SavedRoom {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let json = fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+        let saved = serde_json::from_str(&json).map_err(|e| format!("{} isn't a lanterm save: {}", path.display(), e))?;
+        Ok(saved)
+    }
+
+    /// Write next to the target and rename over it - a crash mid-save never leaves half a file
+    pub fn write(&self, path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Names of the players whose seats are waiting for them
+    pub fn player_names(&self) -> Vec<&str> {
+        self.seats.iter().filter(|s| !s.bot).map(|s| s.name.as_str()).collect()
+    }
+}
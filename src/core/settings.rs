@@ -0,0 +1,95 @@
+/// Game settings schemas - what `host --set` accepts, checked and filled in before any room opens
+use std::fmt;
+
+use crate::core::context::Settings;
+use crate::core::game::WebSocketGame;
+
+/// What kind of value a setting takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    /// A whole number from `min` to `max`, both included
+    Number { min: i64, max: i64 },
+    /// on or off - yes/no, true/false and 1/0 work too
+    Switch,
+    /// Free text - the game makes sense of it in `check_settings`
+    Text,
+}
+
+impl fmt::Display for SettingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingKind::Number { min, max } => write!(f, "number {}-{}", min, max),
+            SettingKind::Switch => write!(f, "on/off"),
+            SettingKind::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// One setting a game declares in `WebSocketGame::SETTINGS`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SettingSpec {
+    pub name: &'static str,
+    pub kind: SettingKind,
+    /// Used when the host doesn't set it - written the way `--set` would take it
+    pub default: &'static str,
+    /// One line for `lanterm list` and the host prompts
+    pub help: &'static str,
+}
+
+impl SettingSpec {
+    /// The value as the game will see it - switches come out as "on"/"off", numbers without padding
+    pub fn check(&self, value: &str) -> Result<String, String> {
+        let value = value.trim();
+        match self.kind {
+            SettingKind::Number { min, max } => value.parse::<i64>().ok()
+                .filter(|n| (min..=max).contains(n))
+                .map(|n| n.to_string())
+                .ok_or_else(|| format!("{} should be a number from {} to {}, not '{}'", self.name, min, max, value)),
+            SettingKind::Switch => match value.to_ascii_lowercase().as_str() {
+                "on" | "yes" | "true" | "1" => Ok("on".to_string()),
+                "off" | "no" | "false" | "0" => Ok("off".to_string()),
+                _ => Err(format!("{} should be on or off, not '{}'", self.name, value)),
+            },
+            SettingKind::Text => Ok(value.to_string()),
+        }
+    }
+
+    /// e.g. "board (number 6-26, default 10) - Squares along each side"
+    pub fn describe(&self) -> String {
+        let default = if self.default.is_empty() { "empty" } else { self.default };
+        format!("{} ({}, default {}) - {}", self.name, self.kind, default, self.help)
+    }
+}
+
+/// The game's full settings - every key checked against its schema, the rest at their defaults,
+/// then whatever cross-checks the game does in `check_settings`
+pub fn resolve<G: WebSocketGame>(given: &Settings) -> Result<Settings, String> {
+    let mut settings = defaults(G::SETTINGS);
+    for (key, value) in given {
+        let Some(spec) = G::SETTINGS.iter().find(|spec| spec.name == key) else {
+            return Err(unknown::<G>(key));
+        };
+        settings.insert(key.clone(), spec.check(value)?);
+    }
+    G::check_settings(&settings)?;
+    Ok(settings)
+}
+
+/// Settings with anything missing at its default - for rooms that were never given any, like a lobby's
+pub fn with_defaults(schema: &[SettingSpec], given: &Settings) -> Settings {
+    let mut settings = defaults(schema);
+    settings.extend(given.iter().map(|(key, value)| (key.clone(), value.clone())));
+    settings
+}
+
+fn defaults(schema: &[SettingSpec]) -> Settings {
+    schema.iter().map(|spec| (spec.name.to_string(), spec.default.to_string())).collect()
+}
+
+fn unknown<G: WebSocketGame>(key: &str) -> String {
+    let names: Vec<&str> = G::SETTINGS.iter().map(|spec| spec.name).collect();
+    match names.as_slice() {
+        [] => format!("{} has no settings, so '{}' can't be set", G::NAME, key),
+        _ => format!("{} has no setting called '{}' - try {}", G::NAME, key, names.join(", ")),
+    }
+}
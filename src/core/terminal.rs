@@ -1,66 +1,413 @@
 /// Terminal rendering context that handles all the low-level terminal stuff
-use crossterm::{QueueableCommand, cursor, terminal, style::{SetForegroundColor, ResetColor, Color}};
+/// Draws into an off-screen cell buffer and only writes the cells that changed since the last flush
+use crossterm::{QueueableCommand, cursor, terminal, style::{SetForegroundColor, ResetColor, Color, Print}};
 use std::io::{stdout, Write, Stdout};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-pub struct TerminalContext {
-    out: Stdout,
+/// Unchanged cells up to this wide get reprinted instead of jumping the cursor over them
+const MAX_REPRINT_GAP: usize = 4;
+
+/// One screen cell
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    /// A glyph (plus any zero-width marks after it) and the columns it covers
+    Glyph { symbol: String, width: usize, color: Color },
+    /// Right half of the wide glyph in the cell to the left
+    Continuation,
+}
+
+impl Cell {
+    fn blank() -> Self {
+        Cell::Glyph { symbol: " ".to_string(), width: 1, color: Color::Reset }
+    }
+}
+
+/// A full screen of cells, row by row
+#[derive(Debug, Clone, PartialEq)]
+struct Buffer {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Buffer {
+    fn new(width: usize, height: usize) -> Self {
+        Self { width, height, cells: vec![Cell::blank(); width * height] }
+    }
+
+    fn clear(&mut self) {
+        self.cells.fill(Cell::blank());
+    }
+
+    fn clear_row(&mut self, row: usize) {
+        if row < self.height {
+            self.cells[row * self.width..(row + 1) * self.width].fill(Cell::blank());
+        }
+    }
+
+    fn get(&self, col: usize, row: usize) -> &Cell {
+        &self.cells[row * self.width + col]
+    }
+
+    fn get_mut(&mut self, col: usize, row: usize) -> &mut Cell {
+        &mut self.cells[row * self.width + col]
+    }
+
+    /// Overwrite a cell without leaving half of a wide glyph behind on either side
+    fn set(&mut self, col: usize, row: usize, cell: Cell) {
+        if *self.get(col, row) == Cell::Continuation && col > 0 {
+            *self.get_mut(col - 1, row) = Cell::blank();
+        }
+        if matches!(self.get(col, row), Cell::Glyph { width: 2, .. }) && col + 1 < self.width {
+            *self.get_mut(col + 1, row) = Cell::blank();
+        }
+        *self.get_mut(col, row) = cell;
+    }
+}
+
+/// Screen drawing handed to renderers - print into it, then `flush` to show the changes
+pub struct TerminalContext<W: Write = Stdout> {
+    out: W,
+    /// Frame being drawn
+    back: Buffer,
+    /// Frame the terminal is showing - `None` until the first flush, which clears the screen
+    front: Option<Buffer>,
+    col: usize,
+    row: usize,
+    color: Color,
+    /// Where the terminal cursor was left after the last flush
+    shown_cursor: Option<(usize, usize)>,
+    /// Follow the real terminal size - off for contexts with a fixed size
+    track_size: bool,
+    /// Between `begin_frame` and `end_frame` - renderer flushes wait for the whole frame
+    holding: bool,
 }
 
 impl TerminalContext {
     pub fn new() -> Self {
-        let mut out = stdout();
-        // Clear screen and position cursor
-        out.queue(cursor::MoveTo(0, 0)).unwrap();
-        out.queue(terminal::Clear(terminal::ClearType::All)).unwrap();
-        
-        Self { out }
+        let (cols, rows) = Self::size();
+        let mut ctx = Self::with_writer(stdout(), cols, rows);
+        ctx.track_size = true;
+        ctx
+    }
+
+    /// Terminal size as (columns, rows) - falls back to 80x24 when it can't be read
+    pub fn size() -> (u16, u16) {
+        terminal::size().unwrap_or((80, 24))
+    }
+}
+
+impl<W: Write> TerminalContext<W> {
+    /// Fixed-size context writing anywhere - handy for capturing the exact bytes a frame emits
+    pub fn with_writer(out: W, cols: u16, rows: u16) -> Self {
+        Self {
+            out,
+            back: Buffer::new(cols as usize, rows as usize),
+            front: None,
+            col: 0,
+            row: 0,
+            color: Color::Reset,
+            shown_cursor: None,
+            track_size: false,
+            holding: false,
+        }
+    }
+
+    /// Everything written so far
+    pub fn writer(&self) -> &W {
+        &self.out
+    }
+
+    pub fn writer_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+
+    /// The frame being drawn as plain text - one line per row, colors dropped, trailing blanks trimmed
+    /// Wide glyphs show up once, so lines read the way they look on screen
+    pub fn snapshot(&self) -> String {
+        let mut lines: Vec<String> = (0..self.back.height)
+            .map(|row| {
+                let line: String = (0..self.back.width)
+                    .filter_map(|col| match self.back.get(col, row) {
+                        Cell::Glyph { symbol, .. } => Some(symbol.as_str()),
+                        Cell::Continuation => None,
+                    })
+                    .collect();
+                line.trim_end().to_string()
+            })
+            .collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
+    /// Columns in the frame
+    pub fn width(&self) -> u16 {
+        self.back.width as u16
+    }
+
+    /// Rows in the frame
+    pub fn height(&self) -> u16 {
+        self.back.height as u16
+    }
+
+    /// Start a frame from a blank screen - `flush` calls are held until `end_frame`
+    pub fn begin_frame(&mut self) {
+        self.follow_size();
+        self.back.clear();
+        self.col = 0;
+        self.row = 0;
+        self.color = Color::Reset;
+        self.holding = true;
+    }
+
+    /// Start a frame on top of the last one - for touching up a line or two
+    pub fn begin_patch(&mut self) {
+        self.follow_size();
+        self.holding = true;
+    }
+
+    /// Finish the frame and write whatever changed
+    pub fn end_frame(&mut self) {
+        self.holding = false;
+        self.present();
     }
 
     /// Clear the entire screen and position cursor
     pub fn clear_screen(&mut self) {
-        self.out.queue(cursor::MoveTo(0, 0)).unwrap();
-        self.out.queue(terminal::Clear(terminal::ClearType::All)).unwrap();
+        self.back.clear();
+        self.col = 0;
+        self.row = 0;
+    }
+
+    /// Move the cursor - columns and rows start at 0
+    pub fn move_to(&mut self, col: u16, row: u16) {
+        self.col = col as usize;
+        self.row = row as usize;
+    }
+
+    /// Wipe the line the cursor is on and return to its start - for redrawing prompts in place
+    pub fn clear_line(&mut self) {
+        self.back.clear_row(self.row);
+        self.col = 0;
     }
 
     /// Print a line with automatic carriage return - no more \r boilerplate!
     pub fn print_line(&mut self, text: &str) {
-        writeln!(self.out, "{}\r", text).unwrap();
+        self.put_str(text);
+        self.newline();
     }
 
     /// Print colored text with automatic reset
     pub fn print_colored(&mut self, text: &str, color: TerminalColor) {
-        self.out.queue(SetForegroundColor(color.into())).unwrap();
-        write!(self.out, "{}", text).unwrap();
-        self.out.queue(ResetColor).unwrap();
+        self.color = color.into();
+        self.put_str(text);
+        self.color = Color::Reset;
     }
 
     /// Print a colored line
     pub fn print_colored_line(&mut self, text: &str, color: TerminalColor) {
-        self.out.queue(SetForegroundColor(color.into())).unwrap();
-        writeln!(self.out, "{}\r", text).unwrap();
-        self.out.queue(ResetColor).unwrap();
+        self.print_colored(text, color);
+        self.newline();
     }
 
     /// Print empty line
     pub fn empty_line(&mut self) {
-        writeln!(self.out, "\r").unwrap();
+        self.newline();
     }
 
     /// Print text without newline
     pub fn print(&mut self, text: &str) {
-        write!(self.out, "{}", text).unwrap();
+        self.put_str(text);
     }
 
-    /// Flush all output at once - call this at the end of render
+    /// Show the changes - call this at the end of render
+    /// Inside a framework frame this waits for `end_frame`, so the screen never shows half a frame
     pub fn flush(&mut self) {
-        self.out.flush().unwrap();
+        if !self.holding {
+            self.present();
+        }
+    }
+
+    fn newline(&mut self) {
+        self.col = 0;
+        self.row += 1;
+    }
+
+    fn put_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.put_char(c);
+        }
+    }
+
+    fn put_char(&mut self, c: char) {
+        match c {
+            '\n' => return self.newline(),
+            '\r' => {
+                self.col = 0;
+                return;
+            }
+            c if c.is_control() => return,
+            _ => {}
+        }
+
+        let (width, row) = (self.back.width, self.row);
+        if row >= self.back.height {
+            return;
+        }
+
+        match c.width().unwrap_or(0) {
+            // Combining marks and variation selectors join the glyph before them
+            0 => self.extend_previous(c),
+            w => {
+                if self.col + w <= width {
+                    self.back.set(self.col, row, Cell::Glyph { symbol: c.to_string(), width: w, color: self.color });
+                    if w == 2 {
+                        self.back.set(self.col + 1, row, Cell::Continuation);
+                    }
+                }
+                // Clipped at the right edge - keep counting so the rest of the line clips too
+                self.col += w;
+            }
+        }
+    }
+
+    /// Attach a zero-width char to the last glyph - it may turn it wide, like ❤ + U+FE0F
+    fn extend_previous(&mut self, c: char) {
+        let row = self.row;
+        if self.col > self.back.width {
+            // The glyph it belongs to was clipped
+            return;
+        }
+        let mut col = self.col;
+        while col > 0 {
+            col -= 1;
+            if *self.back.get(col, row) == Cell::Continuation {
+                continue;
+            }
+            if let Cell::Glyph { symbol, width, .. } = self.back.get_mut(col, row) {
+                symbol.push(c);
+                let grown = symbol.width().max(*width);
+                if grown > *width {
+                    *width = grown;
+                    if col + 1 < self.back.width {
+                        self.back.set(col + 1, row, Cell::Continuation);
+                    }
+                    self.col = col + grown;
+                }
+            }
+            return;
+        }
+    }
+
+    /// Match the buffer to the real terminal - a resize forces a full redraw
+    fn follow_size(&mut self) {
+        if !self.track_size {
+            return;
+        }
+        let (cols, rows) = terminal::size().unwrap_or((80, 24));
+        self.resize(cols, rows);
+    }
+
+    /// Start over at a new size - the next flush clears the screen and draws everything
+    fn resize(&mut self, cols: u16, rows: u16) {
+        if (cols as usize, rows as usize) != (self.back.width, self.back.height) {
+            self.back = Buffer::new(cols as usize, rows as usize);
+            self.front = None;
+        }
+    }
+
+    /// Write the cells that differ from what the terminal shows
+    fn present(&mut self) {
+        let _ = self.write_diff();
+        let _ = self.out.flush();
+    }
+
+    fn write_diff(&mut self) -> std::io::Result<()> {
+        let front = match self.front.take() {
+            Some(front) if front.width == self.back.width && front.height == self.back.height => front,
+            _ => {
+                // First frame or a resize - start from a known blank screen
+                self.out.queue(ResetColor)?;
+                self.out.queue(terminal::Clear(terminal::ClearType::All))?;
+                self.shown_cursor = None;
+                Buffer::new(self.back.width, self.back.height)
+            }
+        };
+
+        let mut pen: Option<(usize, usize)> = None;
+        let mut pen_color: Option<Color> = None;
+
+        for row in 0..self.back.height {
+            for col in 0..self.back.width {
+                if self.back.get(col, row) == front.get(col, row) {
+                    continue;
+                }
+                if *self.back.get(col, row) == Cell::Continuation {
+                    // Covered by the wide glyph to its left
+                    continue;
+                }
+
+                match pen {
+                    Some((pen_col, pen_row)) if pen_row == row && pen_col == col => {}
+                    // A short gap is cheaper to reprint than to jump over
+                    Some((pen_col, pen_row)) if pen_row == row && pen_col < col && col - pen_col <= MAX_REPRINT_GAP => {
+                        for gap_col in pen_col..col {
+                            self.emit_cell(gap_col, row, &mut pen_color)?;
+                        }
+                    }
+                    _ => {
+                        self.out.queue(cursor::MoveTo(col as u16, row as u16))?;
+                    }
+                }
+                let width = self.emit_cell(col, row, &mut pen_color)?;
+                pen = Some((col + width, row));
+            }
+        }
+
+        if pen_color.is_some_and(|c| c != Color::Reset) {
+            self.out.queue(ResetColor)?;
+        }
+
+        // Leave the terminal cursor where drawing stopped - e.g. the end of an input line
+        let cursor_at = (self.col.min(self.back.width.saturating_sub(1)), self.row.min(self.back.height.saturating_sub(1)));
+        if pen == Some(cursor_at) {
+            // The last print already left it there
+            self.shown_cursor = Some(cursor_at);
+        } else if pen.is_some() || self.shown_cursor != Some(cursor_at) {
+            self.out.queue(cursor::MoveTo(cursor_at.0 as u16, cursor_at.1 as u16))?;
+            self.shown_cursor = Some(cursor_at);
+        }
+
+        self.front = Some(self.back.clone());
+        Ok(())
+    }
+
+    /// Print one back-buffer cell at the terminal cursor - returns the columns it covered
+    fn emit_cell(&mut self, col: usize, row: usize, pen_color: &mut Option<Color>) -> std::io::Result<usize> {
+        let Cell::Glyph { symbol, width, color } = self.back.get(col, row) else {
+            return Ok(0);
+        };
+        if *pen_color != Some(*color) {
+            self.out.queue(SetForegroundColor(*color))?;
+            *pen_color = Some(*color);
+        }
+        self.out.queue(Print(symbol))?;
+        Ok(*width)
+    }
+}
+
+impl Default for TerminalContext {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 #[derive(Clone, Copy)]
 pub enum TerminalColor {
     Red,
-    Green, 
+    Green,
     Blue,
     Yellow,
     Cyan,
@@ -80,4 +427,160 @@ impl From<TerminalColor> for Color {
             TerminalColor::Default => Color::Reset,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(cols: u16, rows: u16) -> TerminalContext<Vec<u8>> {
+        TerminalContext::with_writer(Vec::new(), cols, rows)
+    }
+
+    /// Draw one whole frame and hand back the bytes it emitted
+    fn frame(ctx: &mut TerminalContext<Vec<u8>>, text: &str) -> Vec<u8> {
+        ctx.writer_mut().clear();
+        ctx.begin_frame();
+        ctx.print(text);
+        ctx.end_frame();
+        ctx.writer().clone()
+    }
+
+    /// What a sequence of commands looks like on the wire
+    fn bytes(queue: impl FnOnce(&mut Vec<u8>) -> std::io::Result<()>) -> Vec<u8> {
+        let mut out = Vec::new();
+        queue(&mut out).unwrap();
+        out
+    }
+
+    fn cleared() -> Vec<u8> {
+        bytes(|out| {
+            out.queue(ResetColor)?;
+            out.queue(terminal::Clear(terminal::ClearType::All))?;
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn first_frame_clears_the_screen() {
+        let mut ctx = context(4, 2);
+        let expected = [cleared(), bytes(|out| {
+            out.queue(cursor::MoveTo(0, 0))?;
+            out.queue(SetForegroundColor(Color::Reset))?;
+            out.queue(Print("h"))?;
+            out.queue(Print("i"))?;
+            Ok(())
+        })].concat();
+        assert_eq!(frame(&mut ctx, "hi"), expected);
+    }
+
+    #[test]
+    fn unchanged_frame_emits_nothing() {
+        let mut ctx = context(4, 2);
+        frame(&mut ctx, "hi");
+        assert!(frame(&mut ctx, "hi").is_empty());
+    }
+
+    #[test]
+    fn single_cell_change_moves_once_and_prints_once() {
+        let mut ctx = context(4, 2);
+        frame(&mut ctx, "hi");
+        let expected = bytes(|out| {
+            out.queue(cursor::MoveTo(1, 0))?;
+            out.queue(SetForegroundColor(Color::Reset))?;
+            out.queue(Print("o"))?;
+            Ok(())
+        });
+        assert_eq!(frame(&mut ctx, "ho"), expected);
+    }
+
+    #[test]
+    fn short_gaps_are_reprinted() {
+        let mut ctx = context(10, 1);
+        frame(&mut ctx, "abcdefgh");
+        let expected = bytes(|out| {
+            out.queue(cursor::MoveTo(1, 0))?;
+            out.queue(SetForegroundColor(Color::Reset))?;
+            for symbol in ["X", "c", "d", "e", "Y"] {
+                out.queue(Print(symbol))?;
+            }
+            out.queue(cursor::MoveTo(8, 0))?;
+            Ok(())
+        });
+        assert_eq!(frame(&mut ctx, "aXcdeYgh"), expected);
+    }
+
+    #[test]
+    fn long_gaps_are_jumped() {
+        let mut ctx = context(10, 1);
+        frame(&mut ctx, "abcdefgh");
+        let gap = 7 - 2;
+        assert!(gap > MAX_REPRINT_GAP);
+        let expected = bytes(|out| {
+            out.queue(cursor::MoveTo(1, 0))?;
+            out.queue(SetForegroundColor(Color::Reset))?;
+            out.queue(Print("X"))?;
+            out.queue(cursor::MoveTo(7, 0))?;
+            out.queue(Print("Y"))?;
+            Ok(())
+        });
+        assert_eq!(frame(&mut ctx, "aXcdefgY"), expected);
+    }
+
+    #[test]
+    fn wide_glyphs_cover_two_columns() {
+        let mut ctx = context(6, 1);
+        let expected = [cleared(), bytes(|out| {
+            out.queue(cursor::MoveTo(0, 0))?;
+            out.queue(SetForegroundColor(Color::Reset))?;
+            out.queue(Print("🚢"))?;
+            out.queue(Print("a"))?;
+            Ok(())
+        })].concat();
+        assert_eq!(frame(&mut ctx, "🚢a"), expected);
+        assert_eq!(*ctx.back.get(1, 0), Cell::Continuation);
+        assert_eq!(ctx.snapshot(), "🚢a");
+    }
+
+    #[test]
+    fn variation_selector_widens_the_glyph_before_it() {
+        let mut ctx = context(6, 1);
+        let expected = [cleared(), bytes(|out| {
+            out.queue(cursor::MoveTo(0, 0))?;
+            out.queue(SetForegroundColor(Color::Reset))?;
+            out.queue(Print("❤\u{fe0f}"))?;
+            out.queue(Print("a"))?;
+            Ok(())
+        })].concat();
+        assert_eq!(frame(&mut ctx, "❤\u{fe0f}a"), expected);
+        assert_eq!(*ctx.back.get(1, 0), Cell::Continuation);
+        assert_eq!(ctx.col, 3);
+    }
+
+    #[test]
+    fn text_clips_at_the_right_edge() {
+        let mut ctx = context(4, 1);
+        let expected = [cleared(), bytes(|out| {
+            out.queue(cursor::MoveTo(0, 0))?;
+            out.queue(SetForegroundColor(Color::Reset))?;
+            for symbol in ["a", "b", "c", "d"] {
+                out.queue(Print(symbol))?;
+            }
+            out.queue(cursor::MoveTo(3, 0))?;
+            Ok(())
+        })].concat();
+        assert_eq!(frame(&mut ctx, "abcdef"), expected);
+
+        // A wide glyph that doesn't fit leaves its column blank
+        frame(&mut ctx, "abc🚢");
+        assert_eq!(ctx.snapshot(), "abc");
+    }
+
+    #[test]
+    fn resize_forces_a_full_redraw() {
+        let mut ctx = context(4, 2);
+        frame(&mut ctx, "hi");
+        ctx.resize(6, 2);
+        assert!(frame(&mut ctx, "hi").starts_with(&cleared()));
+    }
+}
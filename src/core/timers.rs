@@ -0,0 +1,41 @@
+/// One-shot game timers - armed from hooks, fired back through `WebSocketGame::on_timer`
+use std::time::Duration;
+
+/// A change a game asked for while one of its hooks ran
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimerRequest {
+    Schedule { key: String, after: Duration },
+    Cancel { key: String },
+}
+
+/// Timer scheduler handed to game hooks - the room applies the requests once the hook returns
+/// Timers are identified by key, so re-scheduling a key simply moves its deadline
+#[derive(Debug, Default)]
+pub struct Timers {
+    requests: Vec<TimerRequest>,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fire `key` once after `after` - replaces any pending timer with the same key
+    pub fn schedule(&mut self, key: impl Into<String>, after: Duration) {
+        self.requests.push(TimerRequest::Schedule { key: key.into(), after });
+    }
+
+    /// Forget a pending timer - no-op if it already fired or never existed
+    pub fn cancel(&mut self, key: impl Into<String>) {
+        self.requests.push(TimerRequest::Cancel { key: key.into() });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Requests in the order the game made them
+    pub fn into_requests(self) -> Vec<TimerRequest> {
+        self.requests
+    }
+}
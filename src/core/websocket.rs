@@ -1,263 +1,212 @@
 /// WebSocket-based game transport - much simpler than TCP!
-use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{RwLock, broadcast};
+use tokio::sync::broadcast::{self, error::RecvError};
 use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
 use tokio_tungstenite::{
     accept_async,
-    tungstenite::protocol::Message
+    tungstenite::protocol::Message,
+    WebSocketStream,
 };
-use futures_util::{SinkExt, StreamExt};
-use serde::{Serialize, Deserialize};
+use futures_util::{stream::{SplitSink, SplitStream}, SinkExt, StreamExt};
 use uuid::Uuid;
 
-/// WebSocket game messages - much cleaner than custom protocol
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum GameMessage<State, Input> {
-    /// Server metadata (sent first on connection)
-    GameMetadata { name: String, version: String, description: String },
-    /// Player joining the game
-    PlayerJoin { name: String },
-    /// Player leaving the game  
-    PlayerLeave,
-    /// Input from player
-    PlayerInput(Input),
-    /// Game state update
-    StateUpdate(State),
-    /// Chat message or notification
-    Message(String),
-    /// Error message
-    Error(String),
-}
+use crate::core::chat::RateLimiter;
+use crate::core::context::PlayerId;
+use crate::core::discovery::{self, Announcement};
+use crate::core::game::WebSocketGame;
+use crate::core::protocol::{self, Capability, Hello, Role, ServerInfo};
+use crate::core::room::{GameRoom, Room};
+use crate::core::save::SavedRoom;
+use crate::core::websocket_host::HostOptions;
+
+pub use crate::core::protocol::GameMetadata;
+
+/// Logged when a connection can't keep up - `lanterm bench` counts these lines
+pub(crate) const LAGGED: &str = "⚠️  Connection fell behind - skipped";
 
-/// Player session info
+/// Player session info - outlives its connection while the seat is held
 #[derive(Debug, Clone)]
 pub struct PlayerSession {
+    /// Stable id handed to the game - kept across reconnects
+    pub id: PlayerId,
     pub name: String,
+    pub role: Role,
+    /// Seat index among players - `None` for spectators
+    pub seat: Option<usize>,
     pub sender: broadcast::Sender<String>,
+    pub resume_token: String,
+    pub connected: bool,
+    /// Bumped on every disconnect so stale grace timers can tell they lost the race
+    pub disconnect_epoch: u64,
+    pub chat_limiter: RateLimiter,
+    /// Connection negotiated `Capability::Chat` - chat frames skip sessions that didn't
+    pub chat: bool,
+    /// Seat played by a `Bot` on the server - no connection behind it
+    pub bot: bool,
+    /// Seat picked back up from a save and not yet reclaimed - the only kind a name alone can take over
+    pub restored: bool,
 }
 
-/// WebSocket game server - event-driven by design!
-pub struct WebSocketGameServer<State, Input> {
+/// WebSocket game server - a single room on its own port, event-driven by design!
+pub struct WebSocketGameServer<G: WebSocketGame> {
     listener: TcpListener,
-    sessions: Arc<RwLock<HashMap<String, PlayerSession>>>,
-    game_state: Arc<RwLock<State>>,
-    state_broadcast: broadcast::Sender<State>,
-    input_broadcast: broadcast::Sender<(String, Input)>, // (player_id, input)
-    game_metadata: GameMetadata,
+    room: Arc<GameRoom<G>>,
+    metadata: GameMetadata,
+    options: HostOptions,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GameMetadata {
-    pub name: String,
-    pub version: String,
-    pub description: String,
-}
-
-impl<State, Input> WebSocketGameServer<State, Input>
-where
-    State: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
-    Input: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
-{
-    pub async fn new(addr: &str, initial_state: State, metadata: GameMetadata) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+impl<G: WebSocketGame> WebSocketGameServer<G> {
+    pub async fn new(addr: &str, metadata: GameMetadata, options: HostOptions) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let listener = TcpListener::bind(addr).await?;
-        let (state_broadcast, _) = broadcast::channel(100);
-        let (input_broadcast, _) = broadcast::channel(100);
+        
+        let room = match &options.resume {
+            Some(path) => GameRoom::restore("main".to_string(), metadata.clone(), options.clone(), SavedRoom::load(path)?)?,
+            None => GameRoom::new("main".to_string(), metadata.clone(), options.clone()),
+        };
+        let room = Arc::new(room);
+        if options.resume.is_none() {
+            for _ in 0..options.bots {
+                room.add_bot().await.map_err(|e| e.message)?;
+            }
+        }
+        GameRoom::start_clock(&room);
+        if let Some(path) = options.save_path() {
+            GameRoom::save_on_signals(&room, path.to_path_buf());
+        }
         
         Ok(Self {
             listener,
-            sessions: Arc::new(RwLock::new(HashMap::new())),
-            game_state: Arc::new(RwLock::new(initial_state)),
-            state_broadcast,
-            input_broadcast,
-            game_metadata: metadata,
+            room,
+            metadata,
+            options,
         })
     }
 
     /// Start the WebSocket server - naturally event-driven!
-    pub async fn run<F>(&mut self, mut input_handler: F) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
-    where
-        F: FnMut(&Input, &mut State, &str) -> String + Send + 'static,
-    {
-        println!("WebSocket game server listening on {}", self.listener.local_addr()?);
+    pub async fn run(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let local_addr = self.listener.local_addr()?;
+        println!("WebSocket game server listening on {}", local_addr);
         
-        // Spawn input handler task
-        let mut input_rx = self.input_broadcast.subscribe();
-        let game_state = Arc::clone(&self.game_state);
-        let state_broadcast = self.state_broadcast.clone();
-        let sessions = Arc::clone(&self.sessions);
-        
-        tokio::spawn(async move {
-            while let Ok((player_id, input)) = input_rx.recv().await {
-                let player_name = {
-                    let sessions_guard = sessions.read().await;
-                    sessions_guard.get(&player_id).map(|s| s.name.clone())
-                };
-                
-                if let Some(name) = player_name {
-                    let response = {
-                        let mut state = game_state.write().await;
-                        input_handler(&input, &mut *state, &name)
-                    };
-                    
-                    // Broadcast updated state to all players
-                    let current_state = game_state.read().await.clone();
-                    let _ = state_broadcast.send(current_state);
-                    
-                    // Send response back to player if needed
-                    if !response.is_empty() {
-                        let sessions_guard = sessions.read().await;
-                        if let Some(session) = sessions_guard.get(&player_id) {
-                            let msg = GameMessage::<State, Input>::Message(response);
-                            let json = serde_json::to_string(&msg).unwrap_or_default();
-                            let _ = session.sender.send(json);
-                        }
+        // Let players on the LAN find us
+        if self.options.announce {
+            let room = Arc::clone(&self.room);
+            let metadata = self.metadata.clone();
+            let id = Uuid::new_v4().to_string();
+            tokio::spawn(async move {
+                discovery::run_beacon(local_addr.ip(), || async {
+                    Announcement {
+                        id: id.clone(),
+                        name: metadata.name.clone(),
+                        version: metadata.version.clone(),
+                        description: metadata.description.clone(),
+                        players: room.session_count().await,
+                        spectators: room.spectator_count().await,
+                        port: local_addr.port(),
                     }
-                }
-            }
-        });
+                }).await
+            });
+        }
         
         // Accept WebSocket connections
         while let Ok((stream, addr)) = self.listener.accept().await {
             println!("New connection from {}", addr);
             
-            let sessions = Arc::clone(&self.sessions);
-            let state_broadcast = self.state_broadcast.clone();
-            let input_broadcast = self.input_broadcast.clone();
-            let game_state = Arc::clone(&self.game_state);
-            
-            tokio::spawn(Self::handle_connection(
-                stream, sessions, state_broadcast, input_broadcast, game_state, self.game_metadata.clone()
-            ));
+            tokio::spawn(Self::handle_connection(stream, Arc::clone(&self.room)));
         }
         
         Ok(())
     }
 
-    /// Handle individual WebSocket connection - pure events!
-    async fn handle_connection(
-        stream: TcpStream,
-        sessions: Arc<RwLock<HashMap<String, PlayerSession>>>,
-        state_broadcast: broadcast::Sender<State>,
-        input_broadcast: broadcast::Sender<(String, Input)>,
-        game_state: Arc<RwLock<State>>,
-        metadata: GameMetadata,
-    ) {
-        let ws_stream = match accept_async(stream).await {
-            Ok(ws) => ws,
-            Err(e) => {
-                eprintln!("Failed to accept WebSocket: {}", e);
-                return;
-            }
+    /// Handle individual WebSocket connection - the room does the game work
+    async fn handle_connection(stream: TcpStream, room: Arc<GameRoom<G>>) {
+        // Nothing happens until the client proves it speaks our protocol and game
+        let Some(mut conn) = accept_connection(stream, ServerInfo::Game(room.metadata())).await else {
+            return;
         };
-
-        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
         let player_id = Uuid::new_v4().to_string();
-        let mut player_name: Option<String> = None;
-        
-        // Create broadcast channel for this player
-        let (player_sender, mut player_receiver) = broadcast::channel::<String>(100);
-        
-        // Send game metadata first thing
-        let metadata_msg = GameMessage::<State, Input>::GameMetadata {
-            name: metadata.name.clone(),
-            version: metadata.version.clone(),
-            description: metadata.description.clone(),
-        };
-        if let Ok(json) = serde_json::to_string(&metadata_msg) {
-            let _ = ws_sender.send(Message::Text(json)).await;
-        }
-        
-        // Subscribe to state updates
-        let mut state_rx = state_broadcast.subscribe();
-        
-        // Spawn task to forward state updates to this player
-        let player_sender_clone = player_sender.clone();
-        let state_task = tokio::spawn(async move {
-            while let Ok(state) = state_rx.recv().await {
-                let msg = GameMessage::<State, Input>::StateUpdate(state);
-                if let Ok(json) = serde_json::to_string(&msg) {
-                    let _ = player_sender_clone.send(json);
-                }
-            }
-        });
-        
-        // Spawn task to send messages to WebSocket
-        let sender_task = tokio::spawn(async move {
-            while let Ok(msg) = player_receiver.recv().await {
-                if ws_sender.send(Message::Text(msg)).await.is_err() {
-                    break;
-                }
-            }
-        });
 
         // Handle incoming WebSocket messages - event-driven!
-        while let Some(msg_result) = ws_receiver.next().await {
+        while let Some(text) = conn.next_text().await {
+            if !room.handle_text(&player_id, &conn.sender, &conn.capabilities, &text).await {
+                // Explicit leave - give up the seat right away
+                room.leave(&player_id).await;
+                return;
+            }
+        }
+
+        // Connection dropped - the room holds the seat for a while
+        room.disconnect(&player_id).await;
+    }
+}
+
+/// A client past the handshake - frames queued on `sender` are pumped to its socket
+pub(crate) struct Connection {
+    pub hello: Hello,
+    /// What both sides agreed on in the handshake
+    pub capabilities: Vec<Capability>,
+    pub sender: broadcast::Sender<String>,
+    receiver: SplitStream<WebSocketStream<TcpStream>>,
+    forwarder: JoinHandle<()>,
+}
+
+impl Connection {
+    /// Next text frame from the client - `None` once it closes or the socket fails
+    pub async fn next_text(&mut self) -> Option<String> {
+        while let Some(msg_result) = self.receiver.next().await {
             match msg_result {
-                Ok(Message::Text(text)) => {
-                    if let Ok(game_msg) = serde_json::from_str::<GameMessage<State, Input>>(&text) {
-                        match game_msg {
-                            GameMessage::PlayerJoin { name } => {
-                                println!("Player '{}' joined", name);
-                                
-                                // Store session
-                                let session = PlayerSession {
-                                    name: name.clone(),
-                                    sender: player_sender.clone(),
-                                };
-                                
-                                sessions.write().await.insert(player_id.clone(), session);
-                                player_name = Some(name);
-                                
-                                // For games like Battleship that need player joining to trigger state updates,
-                                // auto-send a dummy input to trigger the game's add_player logic
-                                // This is a temporary hack - in the future we might add a proper join hook
-                                let dummy_input_json = r#"{"Fire":{"row":99,"col":99}}"#; // Invalid coords that will just add player
-                                if let Ok(dummy_input) = serde_json::from_str(&dummy_input_json) {
-                                    let _ = input_broadcast.send((player_id.clone(), dummy_input));
-                                }
-                                
-                                // Send current state to new player
-                                let current_state = game_state.read().await.clone();
-                                let state_msg = GameMessage::<State, Input>::StateUpdate(current_state);
-                                if let Ok(json) = serde_json::to_string(&state_msg) {
-                                    let _ = player_sender.send(json);
-                                }
-                            }
-                            
-                            GameMessage::PlayerInput(input) => {
-                                // Forward to input handler
-                                let _ = input_broadcast.send((player_id.clone(), input));
-                            }
-                            
-                            GameMessage::PlayerLeave => {
-                                break;
-                            }
-                            
-                            _ => {
-                                // Handle other message types
-                            }
-                        }
-                    }
-                }
-                Ok(Message::Close(_)) => {
-                    break;
-                }
+                Ok(Message::Text(text)) => return Some(text),
+                Ok(Message::Close(_)) => return None,
                 Err(e) => {
                     eprintln!("WebSocket error: {}", e);
-                    break;
+                    return None;
                 }
-                _ => {}
+                _ => continue,
             }
         }
-        
-        // Cleanup when connection closes
-        if let Some(name) = player_name {
-            println!("Player '{}' disconnected", name);
-            sessions.write().await.remove(&player_id);
+        None
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.forwarder.abort();
+    }
+}
+
+/// Upgrade a TCP connection, shake hands as `server` and start forwarding its queue
+/// `None` if either step failed - a refused client has already been sent its `Reject`
+pub(crate) async fn accept_connection(stream: TcpStream, server: ServerInfo) -> Option<Connection> {
+    // Frames are small and come in bursts - don't let Nagle hold the second one back
+    let _ = stream.set_nodelay(true);
+    let ws_stream = match accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            eprintln!("Failed to accept WebSocket: {}", e);
+            return None;
+        }
+    };
+
+    let (mut ws_sender, mut receiver) = ws_stream.split();
+    let (hello, capabilities) = protocol::accept_handshake(&mut ws_sender, &mut receiver, server).await?;
+
+    let (sender, queue) = broadcast::channel::<String>(100);
+    let forwarder = tokio::spawn(forward_to_socket(queue, ws_sender));
+    Some(Connection { hello, capabilities, sender, receiver, forwarder })
+}
+
+/// Pump a connection's queue into its socket until either side closes
+/// A reader too slow for the queue skips ahead instead of stalling - the next view is a whole state anyway
+async fn forward_to_socket(mut queue: broadcast::Receiver<String>, mut ws_sender: SplitSink<WebSocketStream<TcpStream>, Message>) {
+    loop {
+        match queue.recv().await {
+            Ok(msg) => {
+                if ws_sender.send(Message::Text(msg)).await.is_err() {
+                    break;
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => eprintln!("{} {} messages", LAGGED, skipped),
+            Err(RecvError::Closed) => break,
         }
-        
-        state_task.abort();
-        sender_task.abort();
     }
-}
\ No newline at end of file
+}
@@ -1,29 +1,75 @@
 /// Generic WebSocket game hosting functionality
 use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use crate::core::websocket::{WebSocketGameServer, GameMetadata};
+use crate::core::context::Settings;
+use crate::core::settings;
 use crate::core::game::WebSocketGame;
 
+/// Hosting knobs shared by single-game servers and lobbies
+#[derive(Debug, Clone)]
+pub struct HostOptions {
+    /// Announce the server on the LAN so `lanterm discover` can find it
+    pub announce: bool,
+    /// How long a dropped player's seat is held for them to reconnect
+    pub reconnect_grace: Duration,
+    /// Seed for room RNGs - `None` picks a random one and logs it
+    pub seed: Option<u64>,
+    /// Write a JSON Lines session log here - lobbies add the room id to the file name
+    pub record: Option<PathBuf>,
+    /// Start from this saved game instead of a fresh one
+    pub resume: Option<PathBuf>,
+    /// Where saves go - `/save`, SIGUSR1 and Ctrl-C write here
+    pub save: Option<PathBuf>,
+    /// Bots seated in every fresh room - resumed games bring back their own
+    pub bots: usize,
+    /// Game options from `--set key=value` - handed to `new_game` for every fresh room
+    pub settings: Settings,
+}
+
+impl HostOptions {
+    /// Save target - `--save`, or the file the game was resumed from
+    pub fn save_path(&self) -> Option<&Path> {
+        self.save.as_deref().or(self.resume.as_deref())
+    }
+}
+
+impl Default for HostOptions {
+    fn default() -> Self {
+        Self {
+            announce: true,
+            reconnect_grace: Duration::from_secs(30),
+            seed: None,
+            record: None,
+            resume: None,
+            save: None,
+            bots: 0,
+            settings: Settings::new(),
+        }
+    }
+}
+
 /// Generic WebSocket game host
 pub struct WebSocketGameHost<G: WebSocketGame> {
     _phantom: PhantomData<G>,
 }
 
 impl<G: WebSocketGame> WebSocketGameHost<G> {
-    pub async fn start(addr: &str, game_name: &str, description: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let initial_state = G::new_game();
-        
+    pub async fn start(addr: &str, game_name: &str, description: &str, options: HostOptions) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Bad settings stop the host here, before anyone can join a game they'd break
+        let options = HostOptions { settings: settings::resolve::<G>(&options.settings)?, ..options };
+
         let metadata = GameMetadata {
             name: game_name.to_string(),
-            version: "1.0.0".to_string(),
+            version: G::VERSION.to_string(),
             description: description.to_string(),
         };
         
-        let mut server = WebSocketGameServer::new(addr, initial_state, metadata).await?;
+        let mut server = WebSocketGameServer::<G>::new(addr, metadata, options).await?;
         
         println!("✅ {} WebSocket server running on ws://{}", game_name, addr);
         
-        server.run(|input: &G::Input, state: &mut G::State, player_name: &str| {
-            G::handle_input(input, state, player_name)
-        }).await
+        server.run().await
     }
 }
\ No newline at end of file
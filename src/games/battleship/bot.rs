@@ -0,0 +1,84 @@
+/// Battleship bot - a probability-density hunter
+use crate::core::bot::Bot;
+use crate::core::context::{GameRng, Player};
+use crate::games::battleship::game::{BattleshipGame, BattleshipInput, BattleshipState, CellState, Phase};
+use rand::seq::SliceRandom;
+
+/// A placement running through a hit counts this much more than one through open water
+const HIT_WEIGHT: u32 = 20;
+
+/// Counts every way the enemy fleet could still be laid out and fires where most of them overlap
+/// Placements through hits weigh far more, so once it finds a ship it finishes it off
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DensityBot;
+
+impl Bot<BattleshipGame> for DensityBot {
+    fn name(&self) -> &str {
+        "Admiral Bot"
+    }
+
+    fn choose(&mut self, view: &BattleshipState, me: &Player, rng: &mut GameRng) -> Option<BattleshipInput> {
+        let seat = view.players.iter().position(|p| p.id == me.id)?;
+        // Random layouts are as good as any against a density hunter - take one and get going
+        if view.phase == Phase::Placement {
+            return match (view.player_boards.get(seat)?.fleet_placed(&view.rules), view.ready.get(seat)?) {
+                (_, true) => None,
+                (false, _) => Some(BattleshipInput::RandomFleet),
+                (true, false) => Some(BattleshipInput::Ready),
+            };
+        }
+        if view.phase != Phase::Battle || view.current_turn != seat {
+            return None;
+        }
+
+        let board = view.player_boards.get(1 - seat)?;
+        // Sunk ships are out of the hunt - their cells count as used up and their sizes no longer fit anywhere
+        let mut grid = board.grid().to_vec();
+        for ship in board.ships().iter().filter(|ship| ship.is_sunk()) {
+            for &(r, c) in ship.cells() {
+                grid[r][c] = CellState::Miss;
+            }
+        }
+        let afloat: Vec<usize> = board.ships().iter().filter(|ship| !ship.is_sunk()).map(|ship| ship.size).collect();
+        let density = density(&grid, &afloat);
+        let size = grid.len();
+        let unknown = (0..size)
+            .flat_map(|row| (0..size).map(move |col| (row, col)))
+            .filter(|&(row, col)| grid[row][col] == CellState::Empty);
+        let best = unknown.clone().map(|(row, col)| density[row][col]).max()?;
+
+        // Ties are common early on - pick one at random so the bot isn't predictable
+        let candidates: Vec<(usize, usize)> = unknown.filter(|&(row, col)| density[row][col] == best).collect();
+        let &(row, col) = candidates.choose(rng)?;
+        Some(BattleshipInput::Fire { row, col })
+    }
+}
+
+/// How many placements of the ships still afloat cover each cell - only cells we haven't fired at count
+fn density(grid: &[Vec<CellState>], afloat: &[usize]) -> Vec<Vec<u32>> {
+    let board_size = grid.len();
+    let mut density = vec![vec![0; board_size]; board_size];
+    for &size in afloat {
+        for row in 0..board_size {
+            for col in 0..board_size {
+                for horizontal in [true, false] {
+                    let cells: Vec<(usize, usize)> = (0..size)
+                        .map(|i| if horizontal { (row, col + i) } else { (row + i, col) })
+                        .collect();
+                    if cells.iter().any(|&(r, c)| r >= board_size || c >= board_size || grid[r][c] == CellState::Miss) {
+                        continue;
+                    }
+
+                    let hits = cells.iter().filter(|&&(r, c)| grid[r][c] == CellState::Hit).count() as u32;
+                    let weight = 1 + HIT_WEIGHT * hits;
+                    for (r, c) in cells {
+                        if grid[r][c] == CellState::Empty {
+                            density[r][c] += weight;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    density
+}
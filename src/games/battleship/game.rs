@@ -1,9 +1,12 @@
 use serde::{Deserialize, Serialize};
+use crate::core::bot::Bot;
 use crate::core::game::WebSocketGame;
+use crate::core::context::{GameContext, GameRng, Player, PlayerId, PlayerTag, Settings};
+use crate::core::settings::SettingSpec;
+use crate::games::battleship::bot::DensityBot;
+use crate::games::battleship::rules::{self, BattleshipRules, ShipClass};
 use rand::Rng;
 
-const BOARD_SIZE: usize = 10;
-
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum CellState {
     Empty,
@@ -12,178 +15,489 @@ pub enum CellState {
     Miss,
 }
 
+/// One ship of a fleet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ship {
+    pub name: String,
+    pub size: usize,
+    /// Cells it covers - an enemy's view leaves them out until the ship is sunk
+    cells: Vec<(usize, usize)>,
+    /// Hits taken - an enemy's view only counts them once the ship is sunk
+    pub hits: usize,
+}
+
+impl Ship {
+    pub fn is_sunk(&self) -> bool {
+        self.hits >= self.size
+    }
+
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+}
+
+/// What a shot did - the sunk ship is named so everyone hears about it
+#[derive(Debug, Clone, PartialEq)]
+enum Shot {
+    Miss,
+    Hit,
+    Sunk(String),
+    /// That cell was already fired at
+    Repeat,
+}
+
+/// Shots a player has fired and how many found a ship
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Gunnery {
+    pub shots: u32,
+    pub hits: u32,
+}
+
+impl Gunnery {
+    /// Share of shots that hit, 0-100
+    pub fn accuracy(&self) -> u32 {
+        (self.hits * 100).checked_div(self.shots).unwrap_or(0)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Board {
-    grid: [[CellState; BOARD_SIZE]; BOARD_SIZE],
-    ships: Vec<(usize, usize)>,
+    grid: Vec<Vec<CellState>>,
+    ships: Vec<Ship>,
 }
 
+/// Random spots to try for each ship, and whole layouts to try, before giving up on a fleet
+const SHIP_ATTEMPTS: usize = 200;
+const FLEET_ATTEMPTS: usize = 50;
+
 impl Board {
-    fn new() -> Self {
+    fn new(size: usize) -> Self {
         Board {
-            grid: [[CellState::Empty; BOARD_SIZE]; BOARD_SIZE],
+            grid: vec![vec![CellState::Empty; size]; size],
             ships: Vec::new(),
         }
     }
 
-    fn place_ship(&mut self, size: usize) {
-        let mut rng = rand::thread_rng();
+    /// Rows and columns
+    pub fn size(&self) -> usize {
+        self.grid.len()
+    }
 
-        loop {
-            let row = rng.gen_range(0..BOARD_SIZE);
-            let col = rng.gen_range(0..BOARD_SIZE);
+    /// A whole fleet laid out at random - `None` if it wouldn't fit after plenty of tries
+    pub(crate) fn random_fleet(rules: &BattleshipRules, rng: &mut GameRng) -> Option<Self> {
+        (0..FLEET_ATTEMPTS).find_map(|_| {
+            let mut board = Board::new(rules.board_size);
+            rules.fleet.iter()
+                .all(|class| board.place_ship(class, rules.touching, rng))
+                .then_some(board)
+        })
+    }
+
+    fn place_ship(&mut self, class: &ShipClass, touching: bool, rng: &mut GameRng) -> bool {
+        for _ in 0..SHIP_ATTEMPTS {
+            let row = rng.gen_range(0..self.size());
+            let col = rng.gen_range(0..self.size());
             let direction = rng.gen::<bool>();
 
-            if self.can_place_ship(row, col, size, direction) {
-                for i in 0..size {
+            if self.can_place_ship(row, col, class.size, direction, touching) {
+                let mut cells = Vec::with_capacity(class.size);
+                for i in 0..class.size {
                     let (r, c) = if direction { (row, col + i) } else { (row + i, col) };
                     self.grid[r][c] = CellState::Ship;
-                    self.ships.push((r, c));
+                    cells.push((r, c));
                 }
-                break;
+                self.ships.push(Ship { name: class.name.clone(), size: class.size, cells, hits: 0 });
+                return true;
             }
         }
+        false
     }
 
-    fn can_place_ship(&self, row: usize, col: usize, size: usize, direction: bool) -> bool {
-        if direction {
-            if col + size > BOARD_SIZE { return false; }
-            for i in 0..size {
-                if self.grid[row][col + i] != CellState::Empty { return false; }
-            }
-        } else {
-            if row + size > BOARD_SIZE { return false; }
-            for i in 0..size {
-                if self.grid[row + i][col] != CellState::Empty { return false; }
-            }
+    /// Set a ship down where the player asked - one already on the board is picked up first, so this moves it
+    fn place_at(&mut self, class: &ShipClass, row: usize, col: usize, horizontal: bool, rules: &BattleshipRules) -> Result<(), String> {
+        if row >= self.size() || col >= self.size() {
+            return Err(format!("Invalid coordinates! Use A1-{}", rules::square(self.size() - 1, self.size() - 1)));
+        }
+
+        let mut board = self.clone();
+        board.remove_ship(&class.name);
+        if !board.can_place_ship(row, col, class.size, horizontal, rules.touching) {
+            let apart = if rules.touching { "" } else { ", or touches one" };
+            return Err(format!(
+                "The {} doesn't fit at {} going {} - it runs off the board or over another ship{}",
+                class.name, rules::square(row, col), if horizontal { "across" } else { "down" }, apart
+            ));
+        }
+
+        let cells: Vec<(usize, usize)> = (0..class.size)
+            .map(|i| if horizontal { (row, col + i) } else { (row + i, col) })
+            .collect();
+        for &(r, c) in &cells {
+            board.grid[r][c] = CellState::Ship;
+        }
+        board.ships.push(Ship { name: class.name.clone(), size: class.size, cells, hits: 0 });
+        // Keep the fleet in its usual order however the player placed it
+        board.ships.sort_by_key(|ship| rules.position(&ship.name));
+        *self = board;
+        Ok(())
+    }
+
+    fn remove_ship(&mut self, name: &str) {
+        let Some(idx) = self.ships.iter().position(|ship| ship.name == name) else {
+            return;
+        };
+        for (r, c) in self.ships.remove(idx).cells {
+            self.grid[r][c] = CellState::Empty;
+        }
+    }
+
+    /// Every ship of the fleet is on the board
+    pub fn fleet_placed(&self, rules: &BattleshipRules) -> bool {
+        rules.fleet.iter().all(|class| self.ship(&class.name).is_some())
+    }
+
+    pub fn ship(&self, name: &str) -> Option<&Ship> {
+        self.ships.iter().find(|ship| ship.name == name)
+    }
+
+    /// On the board and over open water - with `touching` off, clear of every other ship too
+    fn can_place_ship(&self, row: usize, col: usize, size: usize, direction: bool, touching: bool) -> bool {
+        let cells: Vec<(usize, usize)> = (0..size)
+            .map(|i| if direction { (row, col + i) } else { (row + i, col) })
+            .collect();
+        if cells.iter().any(|&(r, c)| r >= self.size() || c >= self.size() || self.grid[r][c] != CellState::Empty) {
+            return false;
         }
-        true
+        touching || cells.iter().all(|&(r, c)| self.neighbours(r, c).all(|(nr, nc)| self.grid[nr][nc] != CellState::Ship))
+    }
+
+    /// The up to eight squares around a square
+    fn neighbours(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let rows = row.saturating_sub(1)..=(row + 1).min(self.size() - 1);
+        rows.flat_map(move |r| {
+            let cols = col.saturating_sub(1)..=(col + 1).min(self.size() - 1);
+            cols.map(move |c| (r, c))
+        })
+        .filter(move |&square| square != (row, col))
     }
 
-    fn fire(&mut self, row: usize, col: usize) -> CellState {
+    fn fire(&mut self, row: usize, col: usize) -> Shot {
         match self.grid[row][col] {
             CellState::Empty => {
                 self.grid[row][col] = CellState::Miss;
-                CellState::Miss
+                Shot::Miss
             },
             CellState::Ship => {
                 self.grid[row][col] = CellState::Hit;
-                CellState::Hit
+                let Some(ship) = self.ships.iter_mut().find(|ship| ship.cells.contains(&(row, col))) else {
+                    return Shot::Hit;
+                };
+                ship.hits += 1;
+                if ship.is_sunk() { Shot::Sunk(ship.name.clone()) } else { Shot::Hit }
             },
-            _ => CellState::Miss,
+            CellState::Hit | CellState::Miss => Shot::Repeat,
         }
     }
 
     fn is_game_over(&self) -> bool {
-        self.ships.iter().all(|&(r, c)| self.grid[r][c] == CellState::Hit)
+        self.ships.iter().all(Ship::is_sunk)
     }
 
-    pub fn grid(&self) -> &[[CellState; BOARD_SIZE]; BOARD_SIZE] {
+    /// Ships not sunk yet - in salvo games that's how many shots the owner gets
+    pub fn afloat(&self) -> usize {
+        self.ships.iter().filter(|ship| !ship.is_sunk()).count()
+    }
+
+    pub fn grid(&self) -> &[Vec<CellState>] {
         &self.grid
     }
+
+    pub fn ships(&self) -> &[Ship] {
+        &self.ships
+    }
+
+    /// Copy of the board as the enemy sees it - unhit ships look like open water
+    /// The fleet list stays, so they know what's left, but only sunk ships show where they were
+    fn fogged(&self) -> Self {
+        let mut grid = self.grid.clone();
+        for cell in grid.iter_mut().flatten() {
+            if *cell == CellState::Ship {
+                *cell = CellState::Empty;
+            }
+        }
+        let ships = self.ships.iter()
+            .map(|ship| if ship.is_sunk() {
+                ship.clone()
+            } else {
+                Ship { cells: Vec::new(), hits: 0, ..ship.clone() }
+            })
+            .collect();
+        Board { grid, ships }
+    }
+}
+
+/// Where the match is - fleets are laid out first, then the shooting starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    /// Players set their ships down and say they're ready
+    Placement,
+    /// Both fleets are locked in - turns of firing
+    Battle,
+    /// Someone's fleet is gone, or a player walked away
+    Over,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BattleshipState {
-    pub players: Vec<String>,
+    /// Board size, fleet and variants - picked by the host, fixed for the match
+    pub rules: BattleshipRules,
+    pub players: Vec<PlayerTag>,
     pub player_boards: Vec<Board>,
+    /// Per seat - whether that player has locked in their fleet
+    pub ready: Vec<bool>,
+    /// Per seat - shots fired and hits landed
+    pub gunnery: Vec<Gunnery>,
+    pub phase: Phase,
     pub current_turn: usize,
+    /// Shots the current player has left this turn - more than one only in salvo games
+    pub shots_left: usize,
     pub message: String,
-    pub finished: bool,
     pub winner: Option<String>,
+    /// Index into `players` of whoever this view was made for - `None` for spectators
+    #[serde(default)]
+    pub viewer: Option<usize>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum BattleshipInput {
+    /// Set one ship down by name or fleet number - placing it again moves it
+    Place { ship: String, row: usize, col: usize, horizontal: bool },
+    /// Lay out the whole fleet at random
+    RandomFleet,
+    /// Done placing - the battle starts once both players are ready
+    Ready,
     Fire { row: usize, col: usize },
 }
 
 impl BattleshipState {
-    pub fn new() -> Self {
+    pub fn new(rules: BattleshipRules) -> Self {
         Self {
+            rules,
             players: Vec::new(),
             player_boards: Vec::new(),
+            ready: Vec::new(),
+            gunnery: Vec::new(),
+            phase: Phase::Placement,
             current_turn: 0,
+            shots_left: 1,
             message: "Welcome to Battleship! Waiting for 2 players...".to_string(),
-            finished: false,
             winner: None,
+            viewer: None,
         }
     }
 
-    pub fn add_player(&mut self, name: String) {
-        if !self.players.contains(&name) && self.players.len() < 2 {
-            self.players.push(name.clone());
-            
-            // Create and set up board for new player
-            let mut board = Board::new();
-            // Place ships: Aircraft Carrier(5), Battleship(4), Cruiser(3), Submarine(3), Destroyer(2)
-            board.place_ship(5);
-            board.place_ship(4);
-            board.place_ship(3);
-            board.place_ship(3);
-            board.place_ship(2);
-            self.player_boards.push(board);
-            
+    /// Seat a player with an empty board - they lay out their own fleet during placement
+    pub fn add_player(&mut self, player: PlayerTag) {
+        if !self.has_player(player.id) && self.players.len() < 2 && self.phase == Phase::Placement {
+            let name = player.name.clone();
+            self.players.push(player);
+            self.player_boards.push(Board::new(self.rules.board_size));
+            self.ready.push(false);
+            self.gunnery.push(Gunnery::default());
+
             if self.players.len() == 2 {
-                self.message = format!("🚢 Battle begins! {} fires first.", self.players[0]);
+                self.message = "⚓ Both admirals are here - place your ships!".to_string();
             } else {
                 self.message = format!("{} joined! Waiting for one more player...", name);
             }
         }
     }
 
-    pub fn fire(&mut self, row: usize, col: usize, player_name: &str) -> Result<String, String> {
-        if self.players.len() < 2 {
-            return Err("Need 2 players to start battle!".to_string());
+    fn seat_of(&self, id: PlayerId) -> Option<usize> {
+        self.players.iter().position(|p| p.id == id)
+    }
+
+    /// The seat of someone who can still move their ships
+    fn placing_seat(&self, id: PlayerId) -> Result<usize, String> {
+        if self.phase != Phase::Placement {
+            return Err("The fleets are already at sea!".to_string());
+        }
+        let seat = self.seat_of(id).ok_or("You must join the game first!")?;
+        if self.ready[seat] {
+            return Err("You're ready - your fleet is locked in.".to_string());
+        }
+        Ok(seat)
+    }
+
+    /// Put one ship where the player asked - by name in any case, or by its number in the fleet
+    pub fn place(&mut self, id: PlayerId, ship: &str, row: usize, col: usize, horizontal: bool) -> Result<String, String> {
+        let seat = self.placing_seat(id)?;
+        let Some(class) = self.rules.class(ship).cloned() else {
+            let names: Vec<&str> = self.rules.fleet.iter().map(|class| class.name.as_str()).collect();
+            return Err(format!("No ship called '{}' - your fleet is {}", ship, names.join(", ")));
+        };
+
+        self.player_boards[seat].place_at(&class, row, col, horizontal, &self.rules)?;
+        Ok(format!("{} placed at {} going {}", class.name, rules::square(row, col), if horizontal { "across" } else { "down" }))
+    }
+
+    /// Lay out the player's whole fleet with the room's RNG
+    pub fn place_randomly(&mut self, id: PlayerId, rng: &mut GameRng) -> Result<String, String> {
+        let seat = self.placing_seat(id)?;
+        self.player_boards[seat] = Board::random_fleet(&self.rules, rng)
+            .ok_or("Couldn't fit the fleet at random - try again, or place the ships by hand")?;
+        Ok("🎲 Fleet laid out at random - type 'ready' to lock it in, or 'random' again to reshuffle".to_string())
+    }
+
+    /// Lock in a player's fleet - once both seats are ready the battle starts
+    pub fn set_ready(&mut self, id: PlayerId) -> Result<String, String> {
+        let seat = self.placing_seat(id)?;
+        let board = &self.player_boards[seat];
+        if !board.fleet_placed(&self.rules) {
+            let missing: Vec<&str> = self.rules.fleet.iter().map(|class| class.name.as_str()).filter(|name| board.ship(name).is_none()).collect();
+            return Err(format!("Still to place: {} - or type 'random'", missing.join(", ")));
+        }
+
+        self.ready[seat] = true;
+        if self.players.len() == 2 && self.ready.iter().all(|&ready| ready) {
+            self.phase = Phase::Battle;
+            self.current_turn = 0;
+            self.shots_left = self.rules.shots(self.player_boards[0].afloat());
+            self.message = if self.rules.salvo {
+                format!("🚢 Battle begins! {} fires first - {} shots a salvo.", self.players[0].name, self.shots_left)
+            } else {
+                format!("🚢 Battle begins! {} fires first.", self.players[0].name)
+            };
+        } else {
+            self.message = format!("✅ {} is ready for battle!", self.players[seat].name);
+        }
+        Ok(self.message.clone())
+    }
+
+    pub fn has_player(&self, id: PlayerId) -> bool {
+        self.players.iter().any(|p| p.id == id)
+    }
+
+    /// A departing player forfeits a running battle, or frees their seat before it starts
+    pub fn remove_player(&mut self, id: PlayerId) {
+        let Some(idx) = self.players.iter().position(|p| p.id == id) else {
+            return;
+        };
+        let name = self.players[idx].name.clone();
+
+        if self.phase == Phase::Over {
+            return;
+        }
+
+        if self.phase == Phase::Battle {
+            let winner = self.players[1 - idx].name.clone();
+            self.phase = Phase::Over;
+            self.message = format!("🏳️ {} abandoned the battle. 🏆 {} wins!", name, winner);
+            self.winner = Some(winner);
+        } else {
+            self.players.remove(idx);
+            self.player_boards.remove(idx);
+            self.ready.remove(idx);
+            self.gunnery.remove(idx);
+            self.message = "Welcome to Battleship! Waiting for 2 players...".to_string();
+        }
+    }
+
+    /// Project the state for one viewer - only their own fleet is revealed
+    pub fn view_for(&self, viewer: PlayerId) -> Self {
+        let mut view = self.clone();
+        view.viewer = self.players.iter().position(|p| p.id == viewer);
+        for (idx, board) in view.player_boards.iter_mut().enumerate() {
+            if view.viewer != Some(idx) {
+                *board = board.fogged();
+            }
+        }
+        view
+    }
+
+    /// Project the state for spectators - fog of war on both fleets until the battle ends
+    pub fn spectator_view(&self) -> Self {
+        if self.phase == Phase::Over {
+            self.clone()
+        } else {
+            self.view_for(PlayerId::NOBODY)
         }
+    }
 
-        if self.finished {
-            return Err("Battle is over!".to_string());
+    pub fn fire(&mut self, row: usize, col: usize, player: PlayerId) -> Result<String, String> {
+        match self.phase {
+            Phase::Placement if self.players.len() < 2 => return Err("Need 2 players to start battle!".to_string()),
+            Phase::Placement => return Err("Ships are still being placed - type 'ready' once your fleet is set".to_string()),
+            Phase::Over => return Err("Battle is over!".to_string()),
+            Phase::Battle => {}
         }
 
         // Validate it's the player's turn
-        let current_player = self.players.get(self.current_turn).unwrap();
-        if current_player != player_name {
-            return Err(format!("Not your turn! It's {}'s turn.", current_player));
+        let current_player = &self.players[self.current_turn];
+        if current_player.id != player {
+            return Err(format!("Not your turn! It's {}'s turn.", current_player.name));
         }
+        let player_name = current_player.name.clone();
 
         // Validate coordinates
-        if row >= BOARD_SIZE || col >= BOARD_SIZE {
-            return Err(format!("Invalid coordinates! Use 0-{}", BOARD_SIZE - 1));
+        let size = self.rules.board_size;
+        if row >= size || col >= size {
+            return Err(format!("Invalid coordinates! Use A1-{}", rules::square(size - 1, size - 1)));
         }
 
         // Fire at opponent's board (opposite player)
         let opponent_idx = 1 - self.current_turn;
-        let result = self.player_boards[opponent_idx].fire(row, col);
-        
-        let result_message = match result {
-            CellState::Hit => {
-                // Check if opponent is defeated
-                if self.player_boards[opponent_idx].is_game_over() {
-                    self.finished = true;
-                    self.winner = Some(player_name.to_string());
-                    format!("🎯 Direct hit! 🏆 {} wins the battle!", player_name)
-                } else {
-                    "🎯 Direct hit! Enemy ship damaged!".to_string()
-                }
-            },
-            CellState::Miss => "💦 Missed! Your shot splashed harmlessly.".to_string(),
-            _ => "🔄 Already fired here!".to_string(),
+        let opponent_name = self.players[opponent_idx].name.clone();
+        let shot = self.player_boards[opponent_idx].fire(row, col);
+        if shot == Shot::Repeat {
+            return Err(format!("🔄 Already fired at {} - pick another spot", rules::square(row, col)));
+        }
+
+        let hit = shot != Shot::Miss;
+        let gunnery = &mut self.gunnery[self.current_turn];
+        gunnery.shots += 1;
+        if hit {
+            gunnery.hits += 1;
+        }
+
+        // Check if opponent is defeated
+        if self.player_boards[opponent_idx].is_game_over() {
+            self.phase = Phase::Over;
+            self.winner = Some(player_name.clone());
+        }
+        let finished = self.phase == Phase::Over;
+
+        let result_message = match shot {
+            Shot::Sunk(ship) if finished => format!("💥 {} sank {}'s {} - 🏆 {} wins the battle!", player_name, opponent_name, ship, player_name),
+            Shot::Sunk(ship) => format!("💥 {} sank {}'s {}!", player_name, opponent_name, ship),
+            Shot::Hit => format!("🎯 {} hit one of {}'s ships!", player_name, opponent_name),
+            _ => format!("💦 {} missed - the shot splashed into {}'s waters.", player_name, opponent_name),
         };
 
-        // Switch turns only if it was a miss or if game is over
-        if result == CellState::Miss || self.finished {
+        // Classic turns end on a miss - a salvo lasts one shot per ship the shooter has afloat, hit or miss
+        let turn_over = if self.rules.salvo {
+            self.shots_left = self.shots_left.saturating_sub(1);
+            self.shots_left == 0
+        } else {
+            !hit
+        };
+        if turn_over || finished {
             self.current_turn = (self.current_turn + 1) % 2;
+            self.shots_left = self.rules.shots(self.player_boards[self.current_turn].afloat());
         }
 
-        if !self.finished && result != CellState::Miss {
-            self.message = format!("{}  {} gets another turn!", result_message, player_name);
-        } else if !self.finished {
-            let next_player = &self.players[self.current_turn];
-            self.message = format!("{}  {}'s turn to fire!", result_message, next_player);
+        let next_player = &self.players[self.current_turn].name;
+        self.message = if finished {
+            result_message
+        } else if !turn_over && self.rules.salvo {
+            format!("{}  {} more in {}'s salvo", result_message, self.shots_left, player_name)
+        } else if !turn_over {
+            format!("{}  {} gets another turn!", result_message, player_name)
+        } else if self.rules.salvo {
+            format!("{}  {}'s salvo - {} shots", result_message, next_player, self.shots_left)
         } else {
-            self.message = result_message;
-        }
+            format!("{}  {}'s turn to fire!", result_message, next_player)
+        };
 
         Ok(self.message.clone())
     }
@@ -195,61 +509,162 @@ pub struct BattleshipGame;
 
 impl WebSocketGame for BattleshipGame {
     type State = BattleshipState;
+    type View = BattleshipState;
     type Input = BattleshipInput;
     
     const NAME: &'static str = "Battleship";
+    const VERSION: &'static str = "5.0.0";
     const DESCRIPTION: &'static str = "Naval combat - sink your opponent's fleet!";
     const MIN_PLAYERS: usize = 2;
     const MAX_PLAYERS: usize = 2;
+    const SETTINGS: &'static [SettingSpec] = rules::SETTINGS;
     
-    fn new_game() -> Self::State {
-        BattleshipState::new()
+    /// Rules come from the room's settings - whoever builds the room has to `settings::resolve` them first,
+    /// since a fleet `check_settings` would refuse has no game to start
+    fn new_game(ctx: &mut GameContext) -> Self::State {
+        BattleshipState::new(BattleshipRules::from_settings(ctx.settings()).expect("room settings weren't resolved"))
+    }
+
+    /// The fleet names real classes and can actually be laid out on the board
+    fn check_settings(settings: &Settings) -> Result<(), String> {
+        BattleshipRules::from_settings(settings).map(|_| ())
     }
     
-    fn handle_input(input: &Self::Input, state: &mut Self::State, player_name: &str) -> String {
-        match input {
-            BattleshipInput::Fire { row, col } => {
-                // Ensure player is in game before firing
-                if !state.players.contains(&player_name.to_string()) {
-                    return "You must join the game first!".to_string();
-                }
-                
-                match state.fire(*row, *col, player_name) {
-                    Ok(message) => message,
-                    Err(error) => error,
-                }
-            }
+    fn handle_input(input: &Self::Input, state: &mut Self::State, ctx: &mut GameContext) {
+        // Ensure player is in game before placing or firing
+        if !state.has_player(ctx.player_id()) {
+            ctx.reply("You must join the game first!");
+            return;
         }
+
+        let player = ctx.player_id();
+        let result = match input {
+            BattleshipInput::Place { ship, row, col, horizontal } => state.place(player, ship, *row, *col, *horizontal),
+            BattleshipInput::RandomFleet => state.place_randomly(player, ctx.rng),
+            BattleshipInput::Ready => state.set_ready(player),
+            BattleshipInput::Fire { row, col } => state.fire(*row, *col, player),
+        };
+        let response = match result {
+            Ok(message) => message,
+            Err(error) => error,
+        };
+        ctx.reply(response);
     }
     
     /// Explicit join handling - much cleaner than magic coordinates!
-    fn on_player_join(state: &mut Self::State, player_name: &str) -> String {
-        if !state.players.contains(&player_name.to_string()) {
-            state.add_player(player_name.to_string());
-            format!("{} joined the battle!", player_name)
-        } else {
-            "You're already in the battle!".to_string()
+    fn on_player_join(state: &mut Self::State, ctx: &mut GameContext) {
+        let Some(player) = ctx.player().map(Player::tag) else { return };
+        if state.has_player(player.id) {
+            ctx.reply("You're already in the battle!");
+            return;
         }
+        ctx.broadcast(format!("{} joined the battle!", player.name));
+        state.add_player(player);
     }
     
-    /// Parse coordinates from line input like "3,4" or "3 4"
+    fn on_player_leave(state: &mut Self::State, ctx: &mut GameContext) {
+        state.remove_player(ctx.player_id());
+        let announcement = format!("{} left the battle.", ctx.player_name());
+        ctx.broadcast(announcement);
+    }
+    
+    /// Opponent boards are fogged - you only see where shots have landed
+    fn view(state: &Self::State, viewer: &Player) -> Self::View {
+        state.view_for(viewer.id)
+    }
+    
+    /// Spectators see shots land on both boards, and every ship once it's over
+    fn spectator_view(state: &Self::State) -> Self::View {
+        state.spectator_view()
+    }
+    
+    /// Probability-density hunter - fills the second seat for solo games
+    fn bot() -> Option<Box<dyn Bot<Self>>> {
+        Some(Box::new(DensityBot))
+    }
+    
+    /// Parse placement commands like "place carrier B4 h", "random" and "ready",
+    /// or a square to fire at - "B7", or row,col counting from 0 like "6,1"
+    /// Whether it's on the board is checked by the game, which knows the board size
     fn parse_line(line: &str) -> Option<Self::Input> {
-        let coords: Result<Vec<usize>, _> = line
-            .trim()
-            .split(|c: char| c == ',' || c.is_whitespace())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.parse())
-            .collect();
-        
-        if let Ok(coords) = coords {
-            if coords.len() == 2 && coords[0] < 10 && coords[1] < 10 {
-                return Some(Self::Input::Fire { 
-                    row: coords[0], 
-                    col: coords[1] 
-                });
+        let words: Vec<&str> = line.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty()).collect();
+        match words.first().map(|w| w.to_ascii_lowercase()).as_deref() {
+            Some("random") if words.len() == 1 => Some(Self::Input::RandomFleet),
+            Some("ready") if words.len() == 1 => Some(Self::Input::Ready),
+            Some("place" | "p") => {
+                let [_, ship, rest @ ..] = words.as_slice() else { return None };
+                let ((row, col), rest) = parse_target(rest)?;
+                let horizontal = match rest {
+                    [] => true,
+                    [way] => match way.to_ascii_lowercase().as_str() {
+                        "h" | "horizontal" | "across" => true,
+                        "v" | "vertical" | "down" => false,
+                        _ => return None,
+                    },
+                    _ => return None,
+                };
+                Some(Self::Input::Place { ship: ship.to_string(), row, col, horizontal })
             }
+            _ => match parse_target(&words)? {
+                ((row, col), []) => Some(Self::Input::Fire { row, col }),
+                _ => None,
+            },
         }
-        None
     }
 }
 
+/// The square the words start with - "B7", or a row and column - and whatever comes after it
+fn parse_target<'a>(words: &'a [&'a str]) -> Option<((usize, usize), &'a [&'a str])> {
+    if let Some(square) = words.first().and_then(|word| rules::parse_square(word)) {
+        return Some((square, &words[1..]));
+    }
+    match words {
+        [row, col, rest @ ..] => Some(((row.parse().ok()?, col.parse().ok()?), rest)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fire(line: &str) -> Option<(usize, usize)> {
+        match BattleshipGame::parse_line(line)? {
+            BattleshipInput::Fire { row, col } => Some((row, col)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn squares_and_row_col_both_fire() {
+        assert_eq!(fire("B7"), Some((6, 1)));
+        assert_eq!(fire("b7"), Some((6, 1)));
+        assert_eq!(fire("6,1"), Some((6, 1)));
+        assert_eq!(fire("6 1"), Some((6, 1)));
+        // Off the board is the game's call, not the parser's
+        assert_eq!(fire("z99"), Some((98, 25)));
+        assert_eq!(fire("B7 now"), None);
+        assert_eq!(fire("7"), None);
+    }
+
+    #[test]
+    fn placement_commands_parse() {
+        match BattleshipGame::parse_line("place 1 B4 v") {
+            Some(BattleshipInput::Place { ship, row, col, horizontal }) => {
+                assert_eq!((ship.as_str(), row, col, horizontal), ("1", 3, 1, false));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match BattleshipGame::parse_line("p carrier 0,0") {
+            Some(BattleshipInput::Place { ship, row, col, horizontal }) => {
+                assert_eq!((ship.as_str(), row, col, horizontal), ("carrier", 0, 0, true));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(BattleshipGame::parse_line("place carrier").is_none());
+        assert!(BattleshipGame::parse_line("place carrier B4 sideways").is_none());
+        assert!(matches!(BattleshipGame::parse_line("Random"), Some(BattleshipInput::RandomFleet)));
+        assert!(matches!(BattleshipGame::parse_line("ready"), Some(BattleshipInput::Ready)));
+        assert!(BattleshipGame::parse_line("ready now").is_none());
+    }
+}
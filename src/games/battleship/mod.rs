@@ -1,6 +1,10 @@
 // Battleship game module exports
 mod game;
 mod renderer;
+mod bot;
+mod rules;
 
-pub use game::BattleshipGame;
-pub use renderer::BattleshipRenderer;
\ No newline at end of file
+pub use game::{BattleshipGame, BattleshipInput, BattleshipState, Phase};
+pub use bot::DensityBot;
+pub use renderer::BattleshipRenderer;
+pub use rules::BattleshipRules;
\ No newline at end of file
@@ -1,129 +1,328 @@
 
+use unicode_width::UnicodeWidthStr;
+
 use crate::core::renderer::GameRenderer;
 use crate::core::terminal::{TerminalContext, TerminalColor};
-use super::game::{BattleshipState, CellState};
+use super::game::{BattleshipState, Board, CellState, Phase};
+use super::rules;
+
+/// Space between boards drawn side by side
+const GAP: usize = 5;
 
-const BOARD_SIZE: usize = 10;
+/// Boards bigger than this get narrower cells so two still fit next to each other
+const WIDE_CELLS_UP_TO: usize = 12;
 
 pub struct BattleshipRenderer {
     player_name: String,
 }
 
+/// One board on screen - what it's called and whether unhit ships are drawn
+struct Panel<'a> {
+    title: String,
+    board: &'a Board,
+    show_ships: bool,
+}
+
+/// One line of a panel - boards side by side draw the same line of each before moving down
+#[derive(Clone, Copy)]
+enum Line {
+    Title,
+    Columns,
+    Row(usize),
+    Ship(usize),
+    Afloat,
+    Blank,
+}
+
 impl GameRenderer<BattleshipState> for BattleshipRenderer {
     fn new(player_name: String) -> Self {
         Self { player_name }
     }
 
     fn render(&self, state: &BattleshipState, ctx: &mut TerminalContext) {
-        // Much cleaner - no manual terminal handling!
         ctx.print_line("🚢 ═══ BATTLESHIP ═══ 🚢");
-        ctx.print_line(&format!("Player: {}", self.player_name));
+        // The view says which seat is ours - spectators don't get one
+        let seat = state.viewer.filter(|&seat| seat < state.players.len());
+        match seat {
+            Some(_) => ctx.print_line(&format!("Player: {}", self.player_name)),
+            None => ctx.print_line(&format!("👀 Spectating as {}", self.player_name)),
+        }
         ctx.empty_line();
-        
-        // Show game status
-        if state.players.len() < 2 {
-            ctx.print_colored_line(&state.message, TerminalColor::Yellow);
-            ctx.print_line(&format!("Players: {}/2", state.players.len()));
-            ctx.flush();
-            return;
+
+        match (seat, state.phase) {
+            // Placement works with one player seated - they can set up while waiting
+            (Some(me), Phase::Placement) => self.render_placement(state, me, ctx),
+            _ if state.players.len() < 2 || state.player_boards.len() < 2 => {
+                ctx.print_colored_line(&state.message, TerminalColor::Yellow);
+                ctx.print_line(&format!("Players: {}/2", state.players.len()));
+                ctx.print_line(&format!("📜 {}", state.rules.summary()));
+            }
+            (None, Phase::Placement) => self.render_spectator_placement(state, ctx),
+            (Some(me), _) => self.render_player(state, me, ctx),
+            (None, _) => self.render_spectator(state, ctx),
         }
+        ctx.flush();
+    }
+}
 
-        // Show players
-        ctx.print_line(&format!("⚔️  {} vs {}", state.players[0], state.players[1]));
+impl BattleshipRenderer {
+    /// Our board with the ships placed so far, what's left to place and how to place it
+    fn render_placement(&self, state: &BattleshipState, me: usize, ctx: &mut TerminalContext) {
+        let opponent = state.players.get(1 - me);
+        match opponent {
+            Some(opponent) => ctx.print_line(&format!("⚔️  You vs {}", opponent.name)),
+            None => ctx.print_line("⚔️  Waiting for an opponent..."),
+        }
+        ctx.print_line(&format!("📜 {}", state.rules.summary()));
         ctx.empty_line();
 
-        // Show current turn or winner
-        if state.finished {
-            if let Some(ref winner) = state.winner {
-                ctx.print_colored_line(&format!("🏆 {} is victorious!", winner), TerminalColor::Green);
-            }
+        let board = &state.player_boards[me];
+        if state.ready[me] {
+            let waiting = opponent.map(|p| p.name.as_str()).unwrap_or("an opponent");
+            ctx.print_colored_line(&format!("✅ You're ready - waiting for {}", waiting), TerminalColor::Green);
         } else {
-            let current_player = &state.players[state.current_turn];
-            ctx.print_colored_line(&format!("🎯 {}'s turn to fire", current_player), TerminalColor::Yellow);
+            ctx.print_colored_line("⚓ Placement - lay out your fleet", TerminalColor::Yellow);
+        }
+        if let Some(opponent) = opponent {
+            let (status, color) = if state.ready[1 - me] { ("ready", TerminalColor::Green) } else { ("still placing", TerminalColor::Yellow) };
+            ctx.print(&format!("   {} is ", opponent.name));
+            ctx.print_colored_line(status, color);
         }
-        
+
         ctx.empty_line();
         ctx.print_line(&state.message);
         ctx.empty_line();
 
-        // Show both boards side by side
-        self.render_boards_side_by_side(state, ctx);
-        
-        if !state.finished {
+        let panel = Panel { title: "⚓ Your fleet".to_string(), board, show_ships: true };
+        for line in [Line::Title, Line::Blank, Line::Columns].into_iter().chain((0..board.size()).map(Line::Row)) {
+            self.draw_line(&panel, line, ctx);
             ctx.empty_line();
-            ctx.print_line("💡 Enter coordinates to fire (row,col):");
-            ctx.print_line("   Example: '3,4' or '3 4' to fire at row 3, column 4");
         }
 
-        ctx.flush();
+        ctx.empty_line();
+        let name_width = state.rules.fleet.iter().map(|class| class.name.width()).max().unwrap_or(0) + 1;
+        let size_width = state.rules.fleet.iter().map(|class| class.size).max().unwrap_or(0) + 1;
+        for (number, class) in state.rules.fleet.iter().enumerate() {
+            ctx.print(&format!("  {:>2}. {:<name_width$}{:<size_width$}", number + 1, class.name, "■".repeat(class.size)));
+            match board.ship(&class.name) {
+                Some(_) => ctx.print_colored_line("✓ placed", TerminalColor::Green),
+                None => ctx.print_colored_line("not placed", TerminalColor::Yellow),
+            }
+        }
+
+        if !state.ready[me] {
+            ctx.empty_line();
+            ctx.print_line("💡 place <ship> <square> <h|v> - e.g. 'place carrier B4 h' or 'place 1 B4 h'");
+            ctx.print_line("   Place a ship again to move it, or type 'random' for the whole fleet");
+            ctx.print_line("   Type 'ready' once your fleet is set");
+        }
     }
-}
 
-impl BattleshipRenderer {
-    fn render_boards_side_by_side(&self, state: &BattleshipState, ctx: &mut TerminalContext) {
-        if state.player_boards.len() != 2 {
-            return;
+    /// Nothing to see on the boards yet - just who's still setting up
+    fn render_spectator_placement(&self, state: &BattleshipState, ctx: &mut TerminalContext) {
+        ctx.print_line(&format!("⚔️  {} vs {}", state.players[0].name, state.players[1].name));
+        ctx.print_line(&format!("📜 {}", state.rules.summary()));
+        ctx.empty_line();
+        ctx.print_colored_line("⚓ The admirals are placing their ships", TerminalColor::Yellow);
+        for (player, &ready) in state.players.iter().zip(&state.ready) {
+            let (status, color) = if ready { ("ready", TerminalColor::Green) } else { ("still placing", TerminalColor::Yellow) };
+            ctx.print(&format!("   {} is ", player.name));
+            ctx.print_colored_line(status, color);
+        }
+        ctx.empty_line();
+        ctx.print_line(&state.message);
+    }
+
+    /// Our fleet on the left, the fog-of-war target map on the right
+    fn render_player(&self, state: &BattleshipState, me: usize, ctx: &mut TerminalContext) {
+        let them = 1 - me;
+        let opponent = &state.players[them].name;
+        ctx.print_line(&format!("⚔️  You vs {}", opponent));
+        ctx.empty_line();
+
+        let over = state.phase == Phase::Over;
+        if over {
+            match &state.winner {
+                Some(winner) if *winner == state.players[me].name => ctx.print_colored_line("🏆 You sank their whole fleet - victory!", TerminalColor::Green),
+                Some(winner) => ctx.print_colored_line(&format!("💀 {} sank your fleet", winner), TerminalColor::Red),
+                None => {}
+            }
+        } else if state.current_turn == me && state.rules.salvo {
+            ctx.print_colored_line(&format!("🎯 Your salvo - {} shots left", state.shots_left), TerminalColor::Yellow);
+        } else if state.current_turn == me {
+            ctx.print_colored_line("🎯 Your turn - fire!", TerminalColor::Yellow);
+        } else {
+            ctx.print_colored_line(&format!("⏳ Waiting for {} to fire", opponent), TerminalColor::Cyan);
+        }
+
+        ctx.empty_line();
+        ctx.print_line(&state.message);
+        ctx.empty_line();
+
+        self.render_panels(
+            [
+                Panel { title: "⚓ Your fleet".to_string(), board: &state.player_boards[me], show_ships: true },
+                Panel { title: format!("🎯 {}'s waters", opponent), board: &state.player_boards[them], show_ships: over },
+            ],
+            ctx,
+        );
+
+        if over {
+            self.render_tally(state, ctx);
+        } else if state.current_turn == me {
+            let last = state.rules.board_size - 1;
+            ctx.empty_line();
+            ctx.print_line(&format!("💡 Enter a square to fire at, from A1 to {}", rules::square(last, last)));
+            ctx.print_line("   Example: 'B7' - or row,col counting from 0, like '6,1'");
         }
+    }
 
-        // Headers - much simpler with terminal context!
-        ctx.print(&format!("{:<25}", format!("🛡️  {}'s Fleet", state.players[0])));
-        ctx.print_line(&format!("🎯 {}'s Targets", state.players[1]));
+    /// Both fleets by name - fogged until the battle is over, since the view hides them anyway
+    fn render_spectator(&self, state: &BattleshipState, ctx: &mut TerminalContext) {
+        ctx.print_line(&format!("⚔️  {} vs {}", state.players[0].name, state.players[1].name));
         ctx.empty_line();
 
-        // Column headers for both boards
-        ctx.print("   ");
-        for i in 0..BOARD_SIZE { ctx.print(&format!(" {} ", i)); }
-        ctx.print("     "); // Space between boards
-        ctx.print("   ");
-        for i in 0..BOARD_SIZE { ctx.print(&format!(" {} ", i)); }
+        let over = state.phase == Phase::Over;
+        if over {
+            if let Some(winner) = &state.winner {
+                ctx.print_colored_line(&format!("🏆 {} is victorious!", winner), TerminalColor::Green);
+            }
+        } else {
+            let current_player = &state.players[state.current_turn].name;
+            ctx.print_colored_line(&format!("🎯 {}'s turn to fire", current_player), TerminalColor::Yellow);
+        }
+
+        ctx.empty_line();
+        ctx.print_line(&state.message);
         ctx.empty_line();
 
-        // Render rows
-        for row in 0..BOARD_SIZE {
-            // Player 0's board (own ships visible)
-            ctx.print(&format!("{:2} ", row));
-            for col in 0..BOARD_SIZE {
-                let cell = state.player_boards[0].grid()[row][col];
-                self.render_cell(cell, false, ctx);
+        self.render_panels(
+            [
+                Panel { title: format!("🌊 {}'s waters", state.players[0].name), board: &state.player_boards[0], show_ships: over },
+                Panel { title: format!("🌊 {}'s waters", state.players[1].name), board: &state.player_boards[1], show_ships: over },
+            ],
+            ctx,
+        );
+
+        if over {
+            self.render_tally(state, ctx);
+        }
+    }
+
+    /// Two boards with each fleet's ships listed underneath - side by side when the terminal is wide enough
+    fn render_panels(&self, panels: [Panel; 2], ctx: &mut TerminalContext) {
+        let size = panels[0].board.size();
+        let ships = panels.iter().map(|panel| panel.board.ships().len()).max().unwrap_or(0);
+        let lines: Vec<Line> = [Line::Title, Line::Blank, Line::Columns].into_iter()
+            .chain((0..size).map(Line::Row))
+            .chain([Line::Blank])
+            .chain((0..ships).map(Line::Ship))
+            .chain([Line::Afloat])
+            .collect();
+
+        let column = board_width(size) + GAP;
+        if column + board_width(size) <= ctx.width() as usize {
+            for &line in &lines {
+                let printed = self.draw_line(&panels[0], line, ctx);
+                ctx.print(&" ".repeat(column.saturating_sub(printed)));
+                self.draw_line(&panels[1], line, ctx);
+                ctx.empty_line();
             }
-            
-            ctx.print("     "); // Space between boards
-            
-            // Player 1's board from player 0's perspective (opponent view - ships hidden)
-            ctx.print(&format!("{:2} ", row));
-            for col in 0..BOARD_SIZE {
-                let cell = state.player_boards[1].grid()[row][col];
-                self.render_cell(cell, true, ctx);
+        } else {
+            for panel in &panels {
+                for &line in &lines {
+                    self.draw_line(panel, line, ctx);
+                    ctx.empty_line();
+                }
+                ctx.empty_line();
             }
-            ctx.empty_line();
         }
 
         ctx.empty_line();
         ctx.print_line("Legend: ■ Ship  ● Hit  · Miss  □ Water");
     }
 
-    fn render_cell(&self, cell: CellState, hide_ships: bool, ctx: &mut TerminalContext) {
-        match cell {
-            CellState::Empty => {
-                if hide_ships {
-                    ctx.print("   ");
-                } else {
-                    ctx.print_colored(" □ ", TerminalColor::Blue);
+    /// Draw one line of a panel without ending it - returns how many columns it took
+    fn draw_line(&self, panel: &Panel, line: Line, ctx: &mut TerminalContext) -> usize {
+        let size = panel.board.size();
+        let cell = cell_width(size);
+        match line {
+            Line::Title => {
+                ctx.print(&panel.title);
+                panel.title.width()
+            }
+            Line::Columns => {
+                let mut header = "   ".to_string();
+                for col in 0..size {
+                    header.push_str(&format!("{:^cell$}", (b'A' + col as u8) as char));
                 }
-            },
-            CellState::Ship => {
-                if hide_ships {
-                    ctx.print("   ");
-                } else {
-                    ctx.print_colored(" ■ ", TerminalColor::White);
+                ctx.print(&header);
+                header.width()
+            }
+            Line::Row(row) => {
+                ctx.print(&format!("{:>2} ", row + 1));
+                for col in 0..size {
+                    self.render_cell(panel.board.grid()[row][col], !panel.show_ships, cell, ctx);
                 }
-            },
-            CellState::Hit => {
-                ctx.print_colored(" ● ", TerminalColor::Red);
-            },
-            CellState::Miss => {
-                ctx.print_colored(" · ", TerminalColor::Cyan);
-            },
+                board_width(size)
+            }
+            Line::Ship(idx) => {
+                let Some(ship) = panel.board.ships().get(idx) else { return 0 };
+                let name_width = panel.board.ships().iter().map(|ship| ship.name.width()).max().unwrap_or(0) + 1;
+                // Hits show up as they land on our own ships - an enemy's only once they've sunk it
+                let hits = ship.hits.min(ship.size);
+                let name = format!("  {:<name_width$}{}{} ", ship.name, "●".repeat(hits), "■".repeat(ship.size - hits));
+                let (status, color) = if ship.is_sunk() {
+                    ("✗ sunk".to_string(), TerminalColor::Red)
+                } else if hits > 0 {
+                    (format!("{} hit", hits), TerminalColor::Yellow)
+                } else {
+                    ("afloat".to_string(), TerminalColor::Green)
+                };
+                ctx.print(&name);
+                ctx.print_colored(&status, color);
+                name.width() + status.width()
+            }
+            Line::Afloat => {
+                let text = format!("  {} of {} afloat", panel.board.afloat(), panel.board.ships().len());
+                ctx.print(&text);
+                text.width()
+            }
+            Line::Blank => 0,
+        }
+    }
+
+    /// Who fired how often and how well - shown once the battle is over
+    fn render_tally(&self, state: &BattleshipState, ctx: &mut TerminalContext) {
+        ctx.empty_line();
+        ctx.print_line("📊 Final tally");
+        for (seat, (player, gunnery)) in state.players.iter().zip(&state.gunnery).enumerate() {
+            let sunk = state.player_boards.get(1 - seat).map(|board| board.ships().iter().filter(|ship| ship.is_sunk()).count()).unwrap_or(0);
+            ctx.print_line(&format!(
+                "   {:<16} {:>3} shots  {:>3} hits  {:>3}% accuracy  {} sunk",
+                player.name, gunnery.shots, gunnery.hits, gunnery.accuracy(), sunk
+            ));
         }
     }
-}
\ No newline at end of file
+
+    fn render_cell(&self, cell: CellState, hide_ships: bool, width: usize, ctx: &mut TerminalContext) {
+        let (glyph, color) = match cell {
+            CellState::Empty | CellState::Ship if hide_ships => (" ", TerminalColor::Default),
+            CellState::Empty => ("□", TerminalColor::Blue),
+            CellState::Ship => ("■", TerminalColor::White),
+            CellState::Hit => ("●", TerminalColor::Red),
+            CellState::Miss => ("·", TerminalColor::Cyan),
+        };
+        ctx.print_colored(&format!("{:^width$}", glyph), color);
+    }
+}
+
+/// Columns each cell takes
+fn cell_width(size: usize) -> usize {
+    if size <= WIDE_CELLS_UP_TO { 3 } else { 2 }
+}
+
+/// Row labels plus the cells
+fn board_width(size: usize) -> usize {
+    3 + cell_width(size) * size
+}
@@ -0,0 +1,290 @@
+/// Battleship rules - board size, fleet and the optional variants, picked with `host --set`
+use serde::{Deserialize, Serialize};
+
+use crate::core::context::{self, Settings};
+use crate::core::settings::{SettingKind, SettingSpec};
+use crate::games::battleship::game::Board;
+
+/// Smallest and largest boards - 26 columns is as far as the letters go
+pub(crate) const MIN_BOARD: usize = 6;
+pub(crate) const MAX_BOARD: usize = 26;
+
+/// Ship classes `fleet=` knows by name - together they're the classic fleet
+const CLASSES: [(&str, usize); 5] = [("Carrier", 5), ("Battleship", 4), ("Cruiser", 3), ("Submarine", 3), ("Destroyer", 2)];
+
+/// What `host --set` takes - `from_settings` turns these into rules
+pub(crate) const SETTINGS: &[SettingSpec] = &[
+    SettingSpec {
+        name: "board",
+        kind: SettingKind::Number { min: MIN_BOARD as i64, max: MAX_BOARD as i64 },
+        default: "10",
+        help: "Squares along each side of the board",
+    },
+    SettingSpec {
+        name: "fleet",
+        kind: SettingKind::Text,
+        default: "Carrier,Battleship,Cruiser,Submarine,Destroyer",
+        help: "Ships by class, or name:size for your own - e.g. carrier,cruiser,dinghy:1",
+    },
+    SettingSpec {
+        name: "salvo",
+        kind: SettingKind::Switch,
+        default: "off",
+        help: "One shot per ship still afloat each turn",
+    },
+    SettingSpec {
+        name: "touching",
+        kind: SettingKind::Switch,
+        default: "on",
+        help: "Ships may sit right next to each other",
+    },
+];
+
+/// One ship every player gets - names are unique within a fleet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShipClass {
+    pub name: String,
+    pub size: usize,
+}
+
+/// How a match is played - fixed when the room opens and kept in the state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BattleshipRules {
+    /// Rows and columns
+    pub board_size: usize,
+    /// Ships each player places, in the order they're listed
+    pub fleet: Vec<ShipClass>,
+    /// One shot per ship still afloat each turn, instead of one shot and another after every hit
+    pub salvo: bool,
+    /// Ships may sit right next to each other - off keeps a square of water around every ship
+    pub touching: bool,
+}
+
+impl Default for BattleshipRules {
+    /// The classic game - 10x10, five ships, one shot a turn
+    fn default() -> Self {
+        Self {
+            board_size: 10,
+            fleet: CLASSES.iter().map(|&(name, size)| ShipClass { name: name.to_string(), size }).collect(),
+            salvo: false,
+            touching: true,
+        }
+    }
+}
+
+impl BattleshipRules {
+    /// Rules from the room's settings - `settings::resolve` already range-checked `board` and turned
+    /// the switches into "on"/"off", so only the fleet and how it fits are left to check
+    pub fn from_settings(settings: &Settings) -> Result<Self, String> {
+        let classic = Self::default();
+        let switch = |key: &str, default: bool| settings.get(key).map_or(default, |value| value == "on");
+        let rules = Self {
+            board_size: settings.get("board").and_then(|size| size.parse().ok()).unwrap_or(classic.board_size),
+            fleet: match settings.get("fleet") {
+                Some(fleet) => parse_fleet(fleet)?,
+                None => classic.fleet,
+            },
+            salvo: switch("salvo", classic.salvo),
+            touching: switch("touching", classic.touching),
+        };
+        rules.check()?;
+        Ok(rules)
+    }
+
+    /// Every ship fits on the board, and the whole fleet can actually be laid out
+    fn check(&self) -> Result<(), String> {
+        if let Some(ship) = self.fleet.iter().find(|ship| ship.size > self.board_size) {
+            return Err(format!("The {} ({} squares) is longer than a {}x{} board", ship.name, ship.size, self.board_size, self.board_size));
+        }
+        // Nobody could ever get out of placement with a fleet that doesn't fit
+        if Board::random_fleet(self, &mut context::seeded_rng(0)).is_none() {
+            let apart = if self.touching { "" } else { " with the ships kept apart" };
+            return Err(format!("That fleet doesn't fit on a {}x{} board{}", self.board_size, self.board_size, apart));
+        }
+        Ok(())
+    }
+
+    /// Shots per turn for a player with `afloat` ships left
+    pub fn shots(&self, afloat: usize) -> usize {
+        if self.salvo { afloat.max(1) } else { 1 }
+    }
+
+    /// A ship of the fleet by name in any case, or by its number in the list
+    pub fn class(&self, name: &str) -> Option<&ShipClass> {
+        let name = name.trim();
+        if let Ok(number) = name.parse::<usize>() {
+            return number.checked_sub(1).and_then(|idx| self.fleet.get(idx));
+        }
+        self.fleet.iter().find(|ship| ship.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Where a ship comes in the fleet list - boards keep their ships in this order
+    pub(crate) fn position(&self, name: &str) -> Option<usize> {
+        self.fleet.iter().position(|ship| ship.name == name)
+    }
+
+    /// One line for the lobby and the placement screen, e.g. "10x10, 5 ships, salvo"
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{}x{}, {} ships", self.board_size, self.board_size, self.fleet.len());
+        if self.salvo {
+            summary.push_str(", salvo");
+        }
+        if !self.touching {
+            summary.push_str(", no touching");
+        }
+        summary
+    }
+}
+
+/// `carrier,cruiser,dinghy:1` - known classes by name, anything else as name:size
+/// A class listed twice gets numbered so every ship can still be placed by name
+fn parse_fleet(value: &str) -> Result<Vec<ShipClass>, String> {
+    let mut fleet: Vec<ShipClass> = Vec::new();
+    for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (name, size) = match entry.split_once(':') {
+            Some((name, size)) => {
+                let size = size.trim().parse().ok()
+                    .filter(|size| (1..=MAX_BOARD).contains(size))
+                    .ok_or_else(|| format!("'{}' needs a ship size from 1 to {}", entry, MAX_BOARD))?;
+                (name.trim().to_string(), size)
+            }
+            None => {
+                let &(name, size) = CLASSES.iter().find(|(name, _)| name.eq_ignore_ascii_case(entry)).ok_or_else(|| {
+                    let known: Vec<&str> = CLASSES.iter().map(|(name, _)| *name).collect();
+                    format!("No ship class called '{}' - use {} or name:size", entry, known.join(", "))
+                })?;
+                (name.to_string(), size)
+            }
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-') {
+            return Err(format!("'{}' isn't a usable ship name - letters, digits and '-' only", name));
+        }
+        // `class` reads a bare number as a place in the fleet, so a ship can't be called one
+        if name.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("'{}' can't be a ship name - numbers pick ships by their place in the fleet", name));
+        }
+
+        let taken = |candidate: &str| fleet.iter().any(|ship| ship.name.eq_ignore_ascii_case(candidate));
+        let name = if fleet.iter().any(|ship| ship.name == name) {
+            // A repeat - the first free `name-N` tells the copies apart
+            (2..).map(|n| format!("{}-{}", name, n)).find(|candidate| !taken(candidate)).unwrap()
+        } else if taken(&name) {
+            return Err(format!("'{}' clashes with another ship - names are matched ignoring case", name));
+        } else {
+            name
+        };
+        fleet.push(ShipClass { name, size });
+    }
+
+    if fleet.is_empty() {
+        return Err("fleet needs at least one ship".to_string());
+    }
+    Ok(fleet)
+}
+
+/// "B7" for row 6, column 1 - the column's letter, then the row counting from 1
+pub fn square(row: usize, col: usize) -> String {
+    let letter = (b'A' + (col % MAX_BOARD) as u8) as char;
+    format!("{}{}", letter, row + 1)
+}
+
+/// Read a square like "B7" or "b7" back - whether it's on the board is up to the game
+pub fn parse_square(text: &str) -> Option<(usize, usize)> {
+    let mut chars = text.chars();
+    let letter = chars.next()?.to_ascii_uppercase();
+    if !letter.is_ascii_uppercase() {
+        return None;
+    }
+    let row: usize = chars.as_str().parse().ok()?;
+    Some((row.checked_sub(1)?, (letter as u8 - b'A') as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::settings;
+    use crate::games::battleship::BattleshipGame;
+
+    fn rules(pairs: &[(&str, &str)]) -> Result<BattleshipRules, String> {
+        let given: Settings = pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+        let resolved = settings::resolve::<BattleshipGame>(&given)?;
+        BattleshipRules::from_settings(&resolved)
+    }
+
+    #[test]
+    fn squares_read_column_letter_then_row() {
+        assert_eq!(parse_square("B7"), Some((6, 1)));
+        assert_eq!(parse_square("b7"), Some((6, 1)));
+        assert_eq!(parse_square("z99"), Some((98, 25)));
+        assert_eq!(parse_square("A0"), None);
+        assert_eq!(parse_square("7B"), None);
+        assert_eq!(parse_square("B"), None);
+        assert_eq!(square(6, 1), "B7");
+        assert_eq!(parse_square(&square(25, 25)), Some((25, 25)));
+    }
+
+    #[test]
+    fn repeated_classes_are_numbered() {
+        let fleet = parse_fleet("cruiser,cruiser, Cruiser").unwrap();
+        let names: Vec<&str> = fleet.iter().map(|ship| ship.name.as_str()).collect();
+        assert_eq!(names, ["Cruiser", "Cruiser-2", "Cruiser-3"]);
+        assert!(fleet.iter().all(|ship| ship.size == 3));
+    }
+
+    #[test]
+    fn numbering_skips_names_already_taken() {
+        let fleet = parse_fleet("a-3:1,a:1,a:1,a:1").unwrap();
+        let names: Vec<&str> = fleet.iter().map(|ship| ship.name.as_str()).collect();
+        assert_eq!(names, ["a-3", "a", "a-2", "a-4"]);
+    }
+
+    #[test]
+    fn names_differing_only_in_case_are_refused() {
+        assert!(parse_fleet("dinghy:1,Dinghy:1").is_err());
+        assert!(parse_fleet("a-2:1,a:1,A:1").is_err());
+    }
+
+    #[test]
+    fn numeric_names_are_refused() {
+        assert!(parse_fleet("7:2").is_err());
+        assert!(parse_fleet("dinghy:1,42:3").is_err());
+        assert!(parse_fleet("pt-109:2").is_ok());
+    }
+
+    #[test]
+    fn custom_ships_take_a_size() {
+        let fleet = parse_fleet("carrier,dinghy:1").unwrap();
+        assert_eq!(fleet[1], ShipClass { name: "dinghy".to_string(), size: 1 });
+        assert!(parse_fleet("dinghy:0").is_err());
+        assert!(parse_fleet("dinghy:27").is_err());
+        assert!(parse_fleet("raft").is_err());
+        assert!(parse_fleet("row boat:2").is_err());
+        assert!(parse_fleet(" , ").is_err());
+    }
+
+    #[test]
+    fn board_sizes_stop_at_6_and_26() {
+        assert_eq!(rules(&[("board", "6")]).unwrap().board_size, 6);
+        assert_eq!(rules(&[("board", "26")]).unwrap().board_size, 26);
+        assert!(rules(&[("board", "5")]).is_err());
+        assert!(rules(&[("board", "27")]).is_err());
+    }
+
+    #[test]
+    fn fleet_has_to_fit_the_board() {
+        let err = rules(&[("board", "6"), ("fleet", "long:7")]).unwrap_err();
+        assert!(err.contains("longer than a 6x6 board"), "{}", err);
+        let err = rules(&[("board", "6"), ("fleet", "carrier,carrier,carrier,carrier,carrier,carrier,carrier,carrier")]).unwrap_err();
+        assert!(err.contains("doesn't fit"), "{}", err);
+    }
+
+    #[test]
+    fn defaults_are_the_classic_game() {
+        let classic = rules(&[]).unwrap();
+        assert_eq!(classic, BattleshipRules::default());
+        assert_eq!(classic.summary(), "10x10, 5 ships");
+        let variant = rules(&[("salvo", "yes"), ("touching", "0")]).unwrap();
+        assert!(variant.salvo && !variant.touching);
+        assert_eq!(variant.shots(3), 3);
+    }
+}
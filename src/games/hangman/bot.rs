@@ -0,0 +1,45 @@
+/// Hangman bot - a letter-frequency guesser
+use std::cmp::Reverse;
+
+use crate::core::bot::Bot;
+use crate::core::context::{GameRng, Player};
+use crate::games::hangman::game::{HangmanGame, HangmanInput, HangmanState, WORDS};
+
+/// English letters from most to least common - breaks ties and covers words it doesn't know
+const LETTER_FREQUENCY: &str = "ETAOINSHRDLCUMWFGYPBVKJXQZ";
+
+/// Guesses the letter found in the most words that still fit the board,
+/// falling back to plain English letter frequency
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrequencyBot;
+
+impl Bot<HangmanGame> for FrequencyBot {
+    fn choose(&mut self, view: &HangmanState, me: &Player, _rng: &mut GameRng) -> Option<HangmanInput> {
+        if view.finished || view.players.len() < 2 {
+            return None;
+        }
+        if view.players.get(view.current_turn)?.id != me.id {
+            return None;
+        }
+
+        let tried = |c: char| view.guessed.contains(&c) || view.wrong.contains(&c);
+        let fits = |word: &str| {
+            word.len() == view.masked_word.len()
+                && word.chars().zip(view.masked_word.chars()).all(|(w, m)| if m == '_' { !tried(w) } else { w == m })
+        };
+
+        // Each fitting word votes once for every letter it still hides
+        let mut votes = [0u32; 26];
+        for word in WORDS.iter().filter(|word| fits(word)) {
+            for letter in LETTER_FREQUENCY.chars().filter(|&c| !tried(c) && word.contains(c)) {
+                votes[(letter as u8 - b'A') as usize] += 1;
+            }
+        }
+
+        LETTER_FREQUENCY.chars()
+            .enumerate()
+            .filter(|&(_, c)| !tried(c))
+            .max_by_key(|&(rank, c)| (votes[(c as u8 - b'A') as usize], Reverse(rank)))
+            .map(|(_, c)| HangmanInput::Guess(c))
+    }
+}
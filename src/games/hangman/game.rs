@@ -1,5 +1,37 @@
 use serde::{Deserialize, Serialize};
+use crate::core::bot::Bot;
 use crate::core::game::WebSocketGame;
+use crate::core::context::{GameContext, Player, PlayerId, PlayerTag, Settings};
+use crate::core::settings::{SettingKind, SettingSpec};
+use rand::seq::SliceRandom;
+use crate::games::hangman::bot::FrequencyBot;
+
+/// Words the game picks from - the bot knows them too
+pub(crate) const WORDS: &[&str] = &["EXAMPLE", "WEBSOCKET", "RUST", "ASYNC", "TOKIO", "HANGMAN", "TERMINAL", "NETWORK", "SOCKET"];
+
+const SETTINGS: &[SettingSpec] = &[
+    SettingSpec {
+        name: "words",
+        kind: SettingKind::Text,
+        default: "",
+        help: "Comma-separated words to pick from - empty uses the built-in list",
+    },
+    SettingSpec {
+        name: "tries",
+        kind: SettingKind::Number { min: 1, max: 12 },
+        default: "6",
+        help: "Wrong guesses allowed before the game is lost",
+    },
+];
+
+/// The host's word list, upper-cased - empty if they didn't give one
+fn custom_words(settings: &Settings) -> Vec<String> {
+    settings.get("words").map(String::as_str).unwrap_or("")
+        .split(',')
+        .map(|word| word.trim().to_uppercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HangmanState {
@@ -8,7 +40,7 @@ pub struct HangmanState {
     pub guessed: Vec<char>,
     pub wrong: Vec<char>,
     pub remaining_tries: u8,
-    pub players: Vec<String>,
+    pub players: Vec<PlayerTag>,
     pub current_turn: usize,
     pub message: String,
     pub finished: bool,
@@ -37,15 +69,50 @@ impl HangmanState {
         }
     }
     
-    pub fn add_player(&mut self, name: String) {
-        if !self.players.contains(&name) {
-            self.players.push(name);
+    pub fn add_player(&mut self, player: PlayerTag) {
+        if !self.players.iter().any(|p| p.id == player.id) {
+            self.players.push(player);
             if self.players.len() == 2 {
-                self.message = format!("Game started! {} goes first.", self.players[0]);
+                self.message = format!("Game started! {} goes first.", self.players[0].name);
             }
         }
     }
     
+    pub fn remove_player(&mut self, id: PlayerId) {
+        let Some(idx) = self.players.iter().position(|p| p.id == id) else {
+            return;
+        };
+        
+        let name = self.players.remove(idx).name;
+        
+        // Keep the turn pointing at the same player, or pass it on if it was theirs
+        if idx < self.current_turn {
+            self.current_turn -= 1;
+        }
+        if self.current_turn >= self.players.len().min(2) {
+            self.current_turn = 0;
+        }
+        
+        if self.finished {
+            return;
+        }
+        
+        if self.players.len() < 2 {
+            self.message = format!("{} left. Waiting for more players...", name);
+        } else {
+            self.message = format!("{} left.  Next: {}", name, self.players[self.current_turn].name);
+        }
+    }
+    
+    /// Copy of the state safe to send to players - the word stays secret until the game ends
+    pub fn redacted(&self) -> Self {
+        let mut view = self.clone();
+        if !self.finished {
+            view.word = String::new();
+        }
+        view
+    }
+    
     fn update_masked_word(&mut self) {
         self.masked_word = self.word.chars()
             .map(|c| {
@@ -62,7 +129,7 @@ impl HangmanState {
             .all(|c| self.guessed.contains(&c))
     }
     
-    pub fn guess(&mut self, letter: char, player_name: &str) -> Result<bool, String> {
+    pub fn guess(&mut self, letter: char, player: PlayerId) -> Result<bool, String> {
         if self.players.len() < 2 {
             return Err("Need at least 2 players to start".to_string());
         }
@@ -71,10 +138,11 @@ impl HangmanState {
             return Err("Game is finished".to_string());
         }
         
-        let current_player = self.players.get(self.current_turn).unwrap();
-        if current_player != player_name {
-            return Err(format!("Not your turn! It's {}'s turn.", current_player));
+        let current_player = &self.players[self.current_turn];
+        if current_player.id != player {
+            return Err(format!("Not your turn! It's {}'s turn.", current_player.name));
         }
+        let player_name = current_player.name.clone();
         
         let letter = letter.to_ascii_uppercase();
         
@@ -115,7 +183,7 @@ impl HangmanState {
         self.current_turn = (self.current_turn + 1) % self.players.len().min(2);
         
         if !self.finished {
-            let next_player = &self.players[self.current_turn];
+            let next_player = &self.players[self.current_turn].name;
             self.message = format!("{}  Next: {}", self.message, next_player);
         }
         
@@ -129,31 +197,57 @@ pub struct HangmanGame;
 
 impl WebSocketGame for HangmanGame {
     type State = HangmanState;
+    type View = HangmanState;
     type Input = HangmanInput;
     
     // Metadata directly in game - no factory needed!
     const NAME: &'static str = "Hangman";
+    const VERSION: &'static str = "2.0.0";
     const DESCRIPTION: &'static str = "Guess the word letter by letter using WebSocket";
     const MIN_PLAYERS: usize = 1;
     const MAX_PLAYERS: usize = 4;
+    const SETTINGS: &'static [SettingSpec] = SETTINGS;
     
-    fn new_game() -> Self::State {
-        let words = ["EXAMPLE", "WEBSOCKET", "RUST", "ASYNC", "TOKIO", "HANGMAN", "TERMINAL", "NETWORK", "SOCKET"];
-        let random_word = words[rand::random::<usize>() % words.len()];
+    fn new_game(ctx: &mut GameContext) -> Self::State {
+        let words = custom_words(ctx.settings());
+        let random_word = match words.choose(ctx.rng) {
+            Some(word) => word.clone(),
+            None => WORDS.choose(ctx.rng).copied().unwrap_or("RUST").to_string(),
+        };
         
         println!("🎯 Secret word: {} (for demo)", random_word);
-        HangmanState::new(random_word.to_string())
+        let mut state = HangmanState::new(random_word);
+        state.remaining_tries = ctx.setting("tries").and_then(|tries| tries.parse().ok()).unwrap_or(state.remaining_tries);
+        state
+    }
+
+    /// Words need at least one letter to guess, and nothing but letters, spaces and '-'
+    fn check_settings(settings: &Settings) -> Result<(), String> {
+        let bad = custom_words(settings).into_iter().find(|word| {
+            !word.chars().any(|c| c.is_ascii_alphabetic()) || !word.chars().all(|c| c.is_ascii_alphabetic() || c == ' ' || c == '-')
+        });
+        match bad {
+            Some(word) => Err(format!("'{}' can't be a Hangman word - letters, spaces and '-' only", word)),
+            None => Ok(()),
+        }
+    }
+    
+    fn on_player_join(state: &mut Self::State, ctx: &mut GameContext) {
+        let Some(player) = ctx.player().map(Player::tag) else { return };
+        ctx.broadcast(format!("👋 {} joined the hangman game!", player.name));
+        state.add_player(player);
     }
     
-    fn on_player_join(state: &mut Self::State, player_name: &str) -> String {
-        state.add_player(player_name.to_string());
-        format!("👋 {} joined the hangman game!", player_name)
+    fn on_player_leave(state: &mut Self::State, ctx: &mut GameContext) {
+        state.remove_player(ctx.player_id());
+        let announcement = format!("👋 {} left the hangman game.", ctx.player_name());
+        ctx.broadcast(announcement);
     }
     
-    fn handle_input(input: &Self::Input, state: &mut Self::State, player_name: &str) -> String {
+    fn handle_input(input: &Self::Input, state: &mut Self::State, ctx: &mut GameContext) {
         match input {
             HangmanInput::Guess(letter) => {
-                match state.guess(*letter, player_name) {
+                let response = match state.guess(*letter, ctx.player_id()) {
                     Ok(correct) => {
                         if correct {
                             format!("✅ Good guess! '{}' is in the word.", letter)
@@ -162,11 +256,22 @@ impl WebSocketGame for HangmanGame {
                         }
                     }
                     Err(e) => e,
-                }
+                };
+                ctx.reply(response);
             }
         }
     }
     
+    /// Everyone sees the same board, just never the secret word
+    fn view(state: &Self::State, _viewer: &Player) -> Self::View {
+        state.redacted()
+    }
+    
+    /// Letter-frequency guesser - lets one person play
+    fn bot() -> Option<Box<dyn Bot<Self>>> {
+        Some(Box::new(FrequencyBot))
+    }
+    
     /// Game developer controls input parsing - no framework interference
     fn parse_line(line: &str) -> Option<Self::Input> {
         // Extract first alphabetic character from line
@@ -1,7 +1,9 @@
 /// Hangman game module - clean and simple!
 pub mod game;
 pub mod renderer;
+pub mod bot;
 
 // Clean exports - game controls its own input parsing!
 pub use game::{HangmanGame, HangmanState};
 pub use renderer::HangmanRenderer;
+pub use bot::FrequencyBot;
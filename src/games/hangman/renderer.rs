@@ -15,10 +15,8 @@ impl GameRenderer<HangmanState> for HangmanRenderer {
     
     /// Pure rendering function - no game logic
     fn render(&self, state: &HangmanState, ctx: &mut TerminalContext) {
-        ctx.clear_screen();
-        
         // Header
-        ctx.print_colored(&format!("🎩 HANGMAN (WebSocket) - Player: {}", self.player_name), TerminalColor::Cyan);
+        ctx.print_colored_line(&format!("🎩 HANGMAN (WebSocket) - Player: {}", self.player_name), TerminalColor::Cyan);
         ctx.print_line("══════════════════════════════════");
         ctx.print_line("");
         
@@ -28,27 +26,28 @@ impl GameRenderer<HangmanState> for HangmanRenderer {
         ctx.print_line(&format!("Tries left: {} {}", state.remaining_tries, "❤".repeat(state.remaining_tries as usize)));
         
         if !state.guessed.is_empty() {
-            ctx.print_colored(&format!("Correct: {}", state.guessed.iter().collect::<String>()), TerminalColor::Green);
+            ctx.print_colored_line(&format!("Correct: {}", state.guessed.iter().collect::<String>()), TerminalColor::Green);
         }
         if !state.wrong.is_empty() {
-            ctx.print_colored(&format!("Wrong: {}", state.wrong.iter().collect::<String>()), TerminalColor::Red);
+            ctx.print_colored_line(&format!("Wrong: {}", state.wrong.iter().collect::<String>()), TerminalColor::Red);
         }
         
         ctx.print_line("");
-        ctx.print_line(&format!("Players: {}", state.players.join(", ")));
+        let names: Vec<&str> = state.players.iter().map(|p| p.name.as_str()).collect();
+        ctx.print_line(&format!("Players: {}", names.join(", ")));
         
         if state.players.len() >= 2 && !state.finished {
-            ctx.print_line(&format!("Current turn: {}", state.players.get(state.current_turn).unwrap_or(&"?".to_string())));
+            let current = state.players.get(state.current_turn).map(|p| p.name.as_str()).unwrap_or("?");
+            ctx.print_line(&format!("Current turn: {}", current));
         }
         
         ctx.print_line("");
-        ctx.print_colored(&format!("📢 {}", state.message), TerminalColor::Yellow);
-        ctx.print_line("");
+        ctx.print_colored_line(&format!("📢 {}", state.message), TerminalColor::Yellow);
         
         if !state.finished && state.players.len() >= 2 {
-            ctx.print_line("💡 Type a letter to guess, or 'q' to quit");
+            ctx.print_line("💡 Type a letter and press Enter to guess - Q counts too!");
         } else if state.finished {
-            ctx.print_line("🏁 Game over! Press 'q' to quit");
+            ctx.print_line("🏁 Game over! Press Ctrl-C or Esc to quit");
         } else {
             ctx.print_line("⏳ Waiting for more players...");
         }
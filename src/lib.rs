@@ -1,4 +1,6 @@
 pub mod core;
 pub mod client;
 pub mod games;
-pub mod cli;
\ No newline at end of file
+pub mod cli;
+pub mod testing;
+pub mod bench;
@@ -0,0 +1,347 @@
+/// Headless test harness - drive a `WebSocketGame` with virtual players, no sockets or terminals
+///
+/// `Simulation` runs the same hooks a `GameRoom` would, in the same order, on a virtual clock,
+/// and `snapshot` captures what a `GameRenderer` draws as plain text for golden-file checks.
+use std::collections::HashMap;
+use std::io::stdout;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tokio::time::Duration;
+
+use crate::core::context::{self, GameContext, GameRng, Outgoing, Player, PlayerId, PlayerRole, Recipient, Settings};
+use crate::core::game::WebSocketGame;
+use crate::core::settings;
+use crate::core::protocol::{ErrorCode, ProtocolError};
+use crate::core::renderer::GameRenderer;
+use crate::core::room::TIMER_RESOLUTION;
+use crate::core::terminal::TerminalContext;
+use crate::core::timers::{TimerRequest, Timers};
+
+/// Snapshot size when none is given - the classic 80x24 terminal
+pub const SNAPSHOT_COLS: u16 = 80;
+pub const SNAPSHOT_ROWS: u16 = 24;
+
+/// Someone in the simulation and everything sent to them
+#[derive(Debug, Clone)]
+pub struct VirtualPlayer {
+    pub id: PlayerId,
+    pub name: String,
+    /// Seat index - `None` for spectators
+    pub seat: Option<usize>,
+    /// Messages in the order they arrived
+    pub messages: Vec<String>,
+}
+
+/// One game instance with virtual players and a virtual clock
+pub struct Simulation<G: WebSocketGame> {
+    state: G::State,
+    players: Vec<VirtualPlayer>,
+    next_id: u64,
+    /// Pending timers by key - deadlines on the virtual clock
+    timers: HashMap<String, Duration>,
+    now: Duration,
+    next_step: Duration,
+    seed: u64,
+    /// Handed to the game's hooks - seeded exactly like a room with `--seed`
+    game_rng: GameRng,
+    /// For the test's own choices - kept apart so scripting doesn't shift the game's rolls
+    rng: StdRng,
+}
+
+impl<G: WebSocketGame> Simulation<G> {
+    /// Fresh game with nobody seated yet
+    pub fn new(seed: u64) -> Self {
+        Self::with_settings(seed, Settings::new())
+    }
+
+    /// Fresh game as if hosted with `--set` - the settings are checked and filled in like on a real host
+    pub fn with_settings(seed: u64, settings: Settings) -> Self {
+        let settings = settings::resolve::<G>(&settings).unwrap_or_else(|e| panic!("{} rejected the settings: {}", G::NAME, e));
+        let mut game_rng = context::seeded_rng(seed);
+        let mut ctx = GameContext::new(&mut game_rng, Duration::ZERO).with_settings(settings);
+        let state = G::new_game(&mut ctx);
+        let (timers, _) = ctx.into_parts();
+
+        let mut sim = Self {
+            state,
+            players: Vec::new(),
+            next_id: 1,
+            timers: HashMap::new(),
+            now: Duration::ZERO,
+            next_step: Self::step_period(),
+            seed,
+            game_rng,
+            rng: StdRng::seed_from_u64(seed),
+        };
+        sim.apply_timers(timers);
+        sim
+    }
+
+    /// Fresh game with `count` players named player1, player2, ... already joined
+    pub fn with_players(count: usize, seed: u64) -> Result<Self, ProtocolError> {
+        let mut sim = Self::new(seed);
+        for n in 1..=count {
+            sim.join(&format!("player{}", n))?;
+        }
+        Ok(sim)
+    }
+
+    /// Seed this simulation was started with - print it when a test fails to replay the run
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Deterministic randomness for picking scripted inputs - separate from the game's RNG
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    /// Seat a player and run the join hook - full games answer with `RoomFull` like a real room
+    /// Names are made unique the same way a room does, so a second "player1" becomes "player1 (2)"
+    pub fn join(&mut self, name: &str) -> Result<PlayerId, ProtocolError> {
+        let seated = self.players.iter().filter(|p| p.seat.is_some()).count();
+        if seated >= G::MAX_PLAYERS {
+            return Err(ProtocolError::new(ErrorCode::RoomFull, format!("All {} seats are taken", G::MAX_PLAYERS)));
+        }
+        let seat = (0..).find(|seat| !self.players.iter().any(|p| p.seat == Some(*seat))).unwrap_or(0);
+        let id = self.add(name, Some(seat));
+
+        let player = self.player_info(id);
+        self.run_hook(player, G::on_player_join);
+        Ok(id)
+    }
+
+    /// Add a spectator - no seat and no game hooks, but they get spectator messages
+    pub fn spectate(&mut self, name: &str) -> PlayerId {
+        self.add(name, None)
+    }
+
+    /// Send one input from a player - returns what this input got sent back to them
+    pub fn input(&mut self, name: &str, input: &G::Input) -> Result<Vec<String>, ProtocolError> {
+        let player = match self.player(name) {
+            Some(player) if player.seat.is_some() => self.player_info(player.id),
+            Some(_) => return Err(ProtocolError::new(ErrorCode::Spectating, format!("{} is only watching", name))),
+            None => return Err(ProtocolError::new(ErrorCode::NotJoined, format!("{} hasn't joined", name))),
+        };
+
+        let before = self.messages(name).len();
+        self.run_hook(player, |state, ctx| G::handle_input(input, state, ctx));
+        Ok(self.messages(name)[before..].to_vec())
+    }
+
+    /// Type a line as a player would - goes through the game's `parse_line` first
+    pub fn send_line(&mut self, name: &str, line: &str) -> Result<Vec<String>, ProtocolError> {
+        match G::parse_line(line) {
+            Some(input) => self.input(name, &input),
+            None => Err(ProtocolError::new(ErrorCode::MalformedFrame, format!("Couldn't understand '{}'", line))),
+        }
+    }
+
+    /// Play a script of (player, line) pairs in order - stops at the first line that fails
+    pub fn script(&mut self, lines: &[(&str, &str)]) -> Result<(), ProtocolError> {
+        for (name, line) in lines {
+            self.send_line(name, line)?;
+        }
+        Ok(())
+    }
+
+    /// Player leaves on purpose - runs the leave hook
+    pub fn leave(&mut self, name: &str) {
+        let Some(index) = self.players.iter().position(|p| p.name == name) else {
+            return;
+        };
+        let player = self.player_info(self.players[index].id);
+        self.players.remove(index);
+
+        if player.is_seated() {
+            self.run_hook(player, G::on_player_leave);
+        }
+    }
+
+    /// Move the virtual clock forward - ticks and timers fire exactly as the room clock would
+    pub fn advance(&mut self, by: Duration) {
+        let until = self.now + by;
+        while self.next_step <= until {
+            self.now = self.next_step;
+            self.step();
+            self.next_step += Self::step_period();
+        }
+        self.now = until;
+    }
+
+    /// Time on the virtual clock since the simulation started
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    pub fn state(&self) -> &G::State {
+        &self.state
+    }
+
+    /// Reach in and set things up - e.g. pick the word before a scripted round
+    pub fn state_mut(&mut self) -> &mut G::State {
+        &mut self.state
+    }
+
+    /// What this player would be sent right now - spectators and strangers get the spectator view
+    pub fn view(&self, name: &str) -> G::View {
+        match self.player(name) {
+            Some(player) if player.seat.is_some() => G::view(&self.state, &self.player_info(player.id)),
+            _ => G::spectator_view(&self.state),
+        }
+    }
+
+    pub fn spectator_view(&self) -> G::View {
+        G::spectator_view(&self.state)
+    }
+
+    pub fn players(&self) -> &[VirtualPlayer] {
+        &self.players
+    }
+
+    pub fn player(&self, name: &str) -> Option<&VirtualPlayer> {
+        self.players.iter().find(|p| p.name == name)
+    }
+
+    /// Everything sent to a player so far
+    pub fn messages(&self, name: &str) -> &[String] {
+        self.player(name).map(|p| p.messages.as_slice()).unwrap_or(&[])
+    }
+
+    /// Hand back and forget a player's messages - handy between script steps
+    pub fn take_messages(&mut self, name: &str) -> Vec<String> {
+        self.players.iter_mut()
+            .find(|p| p.name == name)
+            .map(|p| std::mem::take(&mut p.messages))
+            .unwrap_or_default()
+    }
+
+    /// Panic unless some message to this player contains `needle`
+    pub fn assert_message(&self, name: &str, needle: &str) {
+        let messages = self.messages(name);
+        assert!(
+            messages.iter().any(|m| m.contains(needle)),
+            "{} never got a message containing {:?} (seed {}) - got {:#?}",
+            name, needle, self.seed, messages
+        );
+    }
+
+    /// Render this player's view the way their client would
+    pub fn snapshot<R: GameRenderer<G::View>>(&self, name: &str) -> String {
+        snapshot(&R::new(name.to_string()), &self.view(name))
+    }
+
+    /// Clock period of a real room - the tick rate, or the timer resolution for input-driven games
+    fn step_period() -> Duration {
+        if G::TICK_RATE > 0 {
+            Duration::from_secs(1) / G::TICK_RATE
+        } else {
+            TIMER_RESOLUTION
+        }
+    }
+
+    /// Register someone under a unique name - returns their new id
+    fn add(&mut self, name: &str, seat: Option<usize>) -> PlayerId {
+        let taken = |candidate: &str| self.players.iter().any(|p| p.name == candidate);
+        let name = if taken(name) {
+            (2..).map(|n| format!("{} ({})", name, n)).find(|c| !taken(c)).unwrap_or_else(|| name.to_string())
+        } else {
+            name.to_string()
+        };
+
+        let id = PlayerId(self.next_id);
+        self.next_id += 1;
+        self.players.push(VirtualPlayer { id, name, seat, messages: Vec::new() });
+        id
+    }
+
+    /// How the game sees someone - the lowest seat hosts, like in a room
+    fn player_info(&self, id: PlayerId) -> Player {
+        let host_seat = self.players.iter().filter_map(|p| p.seat).min();
+        let Some(player) = self.players.iter().find(|p| p.id == id) else {
+            return Player::nobody();
+        };
+        let role = match player.seat {
+            Some(seat) if Some(seat) == host_seat => PlayerRole::Host,
+            Some(_) => PlayerRole::Player,
+            None => PlayerRole::Spectator,
+        };
+        Player { id, name: player.name.clone(), seat: player.seat, role }
+    }
+
+    /// One clock step - same order as `GameRoom`: tick, then due timers earliest first
+    fn step(&mut self) {
+        let mut due: Vec<(Duration, String)> = self.timers.iter()
+            .filter(|(_, deadline)| **deadline <= self.now)
+            .map(|(key, deadline)| (*deadline, key.clone()))
+            .collect();
+        due.sort();
+        for (_, key) in &due {
+            self.timers.remove(key);
+        }
+
+        let mut ctx = GameContext::new(&mut self.game_rng, self.now);
+        if G::TICK_RATE > 0 {
+            G::tick(&mut self.state, &mut ctx);
+        }
+        for (_, key) in due {
+            G::on_timer(&mut self.state, &key, &mut ctx);
+        }
+        let (timers, outbox) = ctx.into_parts();
+        self.apply_timers(timers);
+        self.deliver(outbox);
+    }
+
+    /// Run a hook for one player, then apply the timers and deliver the messages it queued
+    fn run_hook(&mut self, player: Player, hook: impl FnOnce(&mut G::State, &mut GameContext)) {
+        let mut ctx = GameContext::for_player(&mut self.game_rng, self.now, player);
+        hook(&mut self.state, &mut ctx);
+        let (timers, outbox) = ctx.into_parts();
+        self.apply_timers(timers);
+        self.deliver(outbox);
+    }
+
+    fn apply_timers(&mut self, timers: Timers) {
+        for request in timers.into_requests() {
+            match request {
+                TimerRequest::Schedule { key, after } => {
+                    self.timers.insert(key, self.now + after);
+                }
+                TimerRequest::Cancel { key } => {
+                    self.timers.remove(&key);
+                }
+            }
+        }
+    }
+
+    fn deliver(&mut self, outbox: Vec<Outgoing>) {
+        for message in outbox {
+            for player in &mut self.players {
+                let wanted = match message.to {
+                    Recipient::Player(id) => player.id == id,
+                    Recipient::Players => player.seat.is_some(),
+                    Recipient::Spectators => player.seat.is_none(),
+                    Recipient::Everyone => true,
+                };
+                if wanted {
+                    player.messages.push(message.text.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Capture what a renderer draws for a view as an 80x24 text grid
+pub fn snapshot<V, R: GameRenderer<V>>(renderer: &R, view: &V) -> String {
+    snapshot_sized(renderer, view, SNAPSHOT_COLS, SNAPSHOT_ROWS)
+}
+
+/// Capture a render at a specific terminal size - lines past the edge are clipped like on screen
+pub fn snapshot_sized<V, R: GameRenderer<V>>(renderer: &R, view: &V, cols: u16, rows: u16) -> String {
+    // The frame is held and never ended, so nothing reaches the real terminal
+    let mut ctx = TerminalContext::with_writer(stdout(), cols, rows);
+    ctx.begin_frame();
+    renderer.render(view, &mut ctx);
+    ctx.snapshot()
+}
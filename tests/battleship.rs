@@ -0,0 +1,94 @@
+//! Battleship through the headless harness - placement, turns, seats, seeds and both renderers' views
+use lanterm::core::protocol::ErrorCode;
+use lanterm::core::recording::{self, Header, Recording};
+use lanterm::core::game::WebSocketGame;
+use lanterm::games::battleship::{BattleshipGame, BattleshipInput, BattleshipRenderer, Phase};
+use lanterm::core::renderer::GameRenderer;
+use lanterm::testing::{snapshot_sized, Simulation, SNAPSHOT_COLS};
+
+/// Tall enough for both boards and the whole fleet panel under them
+const SCREEN_ROWS: u16 = 40;
+
+/// Both fleets laid out at random and locked in
+fn battle(seed: u64) -> Simulation<BattleshipGame> {
+    let mut sim = Simulation::<BattleshipGame>::new(seed);
+    sim.join("alice").unwrap();
+    sim.join("bob").unwrap();
+    sim.script(&[("alice", "random"), ("bob", "random"), ("alice", "ready"), ("bob", "ready")]).unwrap();
+    sim
+}
+
+#[test]
+fn third_player_finds_the_room_full() {
+    let mut sim = Simulation::<BattleshipGame>::with_players(2, 3).unwrap();
+    assert_eq!(sim.join("carol").unwrap_err().code, ErrorCode::RoomFull);
+}
+
+#[test]
+fn battle_starts_once_both_fleets_are_ready() {
+    let mut sim = Simulation::<BattleshipGame>::with_players(2, 3).unwrap();
+    sim.send_line("player1", "random").unwrap();
+    sim.send_line("player1", "ready").unwrap();
+    assert_eq!(sim.state().phase, Phase::Placement);
+
+    sim.send_line("player2", "random").unwrap();
+    sim.send_line("player2", "ready").unwrap();
+    assert_eq!(sim.state().phase, Phase::Battle);
+}
+
+#[test]
+fn players_fire_in_turn() {
+    let mut sim = battle(3);
+    assert_eq!(sim.state().current_turn, 0);
+
+    let replies = sim.send_line("bob", "A1").unwrap();
+    assert!(replies.iter().any(|r| r.contains("Not your turn")), "{:?}", replies);
+    assert_eq!(sim.state().gunnery[1].shots, 0);
+
+    sim.input("alice", &BattleshipInput::Fire { row: 0, col: 0 }).unwrap();
+    assert_eq!(sim.state().gunnery[0].shots, 1);
+
+    // Classic rules: a hit shoots again, a miss passes the turn
+    let hit = sim.state().gunnery[0].hits == 1;
+    assert_eq!(sim.state().current_turn, if hit { 0 } else { 1 });
+}
+
+#[test]
+fn same_seed_lays_out_the_same_fleets() {
+    let fleets = |seed| serde_json::to_value(&battle(seed).state().player_boards).unwrap();
+    assert_eq!(fleets(11), fleets(11));
+}
+
+#[test]
+fn views_match_golden_snapshots() {
+    let mut sim = battle(5);
+    sim.script(&[("alice", "A1")]).unwrap();
+    // Bob sinks Alice's destroyer, so the fleet panel has something to report
+    let destroyer = sim.state().player_boards[0].ship("Destroyer").unwrap().cells().to_vec();
+    for (row, col) in destroyer {
+        sim.input("bob", &BattleshipInput::Fire { row, col }).unwrap();
+    }
+    let screen = |name: &str| snapshot_sized(&BattleshipRenderer::new(name.to_string()), &sim.view(name), SNAPSHOT_COLS, SCREEN_ROWS);
+    assert_eq!(screen("alice"), include_str!("snapshots/battleship_alice.txt").trim_end());
+    assert_eq!(screen("watcher"), include_str!("snapshots/battleship_spectator.txt").trim_end());
+}
+
+#[test]
+fn verify_refuses_settings_the_game_never_allowed() {
+    // A fleet that can't fit on the board - a host would have turned it down before the game started
+    let settings = [("board".to_string(), "5".to_string()), ("fleet".to_string(), "carrier,carrier,carrier,carrier,carrier,carrier".to_string())];
+    let recording = Recording {
+        header: Header {
+            game: BattleshipGame::NAME.to_string(),
+            version: BattleshipGame::VERSION.to_string(),
+            room: "main".to_string(),
+            seed: 1,
+            settings: settings.into_iter().collect(),
+            started_at: chrono::Utc::now(),
+            resumed: false,
+            state: serde_json::Value::Null,
+        },
+        entries: Vec::new(),
+    };
+    assert!(recording::verify::<BattleshipGame>(&recording).is_err());
+}
@@ -0,0 +1,79 @@
+//! Hangman through the headless harness - turns, seats, seeds and what the board looks like
+use lanterm::core::protocol::ErrorCode;
+use lanterm::games::hangman::game::HangmanInput;
+use lanterm::games::hangman::{HangmanGame, HangmanRenderer, HangmanState};
+use lanterm::testing::Simulation;
+
+/// Two players guessing at a known word
+fn rust_game() -> Simulation<HangmanGame> {
+    let mut sim = Simulation::<HangmanGame>::new(42);
+    *sim.state_mut() = HangmanState::new("RUST".to_string());
+    sim.join("alice").unwrap();
+    sim.join("bob").unwrap();
+    sim
+}
+
+#[test]
+fn players_take_turns_in_join_order() {
+    let mut sim = rust_game();
+    assert_eq!(sim.players()[0].seat, Some(0));
+    assert_eq!(sim.players()[1].seat, Some(1));
+
+    let replies = sim.send_line("alice", "r").unwrap();
+    assert!(replies.iter().any(|r| r.contains("Good guess")), "{:?}", replies);
+
+    let replies = sim.send_line("alice", "u").unwrap();
+    assert!(replies.iter().any(|r| r.contains("Not your turn")), "{:?}", replies);
+    assert_eq!(sim.state().masked_word, "R___");
+
+    sim.send_line("bob", "u").unwrap();
+    assert_eq!(sim.state().masked_word, "RU__");
+    assert_eq!(sim.state().current_turn, 0);
+}
+
+#[test]
+fn full_room_turns_players_away() {
+    let mut sim = Simulation::<HangmanGame>::with_players(4, 1).unwrap();
+    let err = sim.join("late").unwrap_err();
+    assert_eq!(err.code, ErrorCode::RoomFull);
+    assert!(sim.player("late").is_none());
+
+    // Spectators don't need a seat
+    sim.spectate("watcher");
+    assert_eq!(sim.player("watcher").unwrap().seat, None);
+}
+
+#[test]
+fn spectators_cannot_guess() {
+    let mut sim = rust_game();
+    sim.spectate("watcher");
+    let err = sim.input("watcher", &HangmanInput::Guess('R')).unwrap_err();
+    assert_eq!(err.code, ErrorCode::Spectating);
+}
+
+#[test]
+fn same_seed_picks_the_same_word() {
+    let play = |seed| {
+        let mut sim = Simulation::<HangmanGame>::with_players(2, seed).unwrap();
+        sim.script(&[("player1", "e"), ("player2", "t"), ("player1", "a")]).unwrap();
+        serde_json::to_value(sim.state()).unwrap()
+    };
+    assert_eq!(play(7), play(7));
+}
+
+#[test]
+fn views_hide_the_word_until_the_end() {
+    let mut sim = rust_game();
+    assert_eq!(sim.view("alice").word, "");
+    sim.script(&[("alice", "r"), ("bob", "u"), ("alice", "s"), ("bob", "t")]).unwrap();
+    assert!(sim.state().finished);
+    assert_eq!(sim.view("alice").word, "RUST");
+    sim.assert_message("alice", "joined");
+}
+
+#[test]
+fn board_matches_golden_snapshot() {
+    let mut sim = rust_game();
+    sim.script(&[("alice", "r"), ("bob", "x")]).unwrap();
+    assert_eq!(sim.snapshot::<HangmanRenderer>("alice"), include_str!("snapshots/hangman_alice.txt").trim_end());
+}
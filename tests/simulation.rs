@@ -0,0 +1,92 @@
+//! The virtual clock - timers and ticks fire when a real room's clock would fire them
+use lanterm::core::context::{GameContext, Player};
+use lanterm::core::game::WebSocketGame;
+use lanterm::testing::Simulation;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+
+/// Arms a five second turn timer on every input, and counts ticks
+#[derive(Clone)]
+struct Clock;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ClockState {
+    ticks: u32,
+    fired: Vec<(String, u64)>,
+}
+
+impl WebSocketGame for Clock {
+    type State = ClockState;
+    type View = ClockState;
+    type Input = String;
+
+    const NAME: &'static str = "Clock";
+    const DESCRIPTION: &'static str = "Timers and ticks";
+    const MIN_PLAYERS: usize = 1;
+    const MAX_PLAYERS: usize = 1;
+    const TICK_RATE: u32 = 10;
+
+    fn new_game(_ctx: &mut GameContext) -> Self::State {
+        ClockState::default()
+    }
+
+    fn handle_input(input: &Self::Input, _state: &mut Self::State, ctx: &mut GameContext) {
+        match input.as_str() {
+            "cancel" => ctx.timers.cancel("turn"),
+            key => ctx.timers.schedule(key, Duration::from_secs(5)),
+        }
+    }
+
+    fn tick(state: &mut Self::State, _ctx: &mut GameContext) -> bool {
+        state.ticks += 1;
+        true
+    }
+
+    fn on_timer(state: &mut Self::State, key: &str, ctx: &mut GameContext) {
+        state.fired.push((key.to_string(), ctx.now().as_millis() as u64));
+        ctx.broadcast(format!("{} is up", key));
+    }
+
+    fn view(state: &Self::State, _viewer: &Player) -> Self::View {
+        state.clone()
+    }
+
+    fn parse_line(line: &str) -> Option<Self::Input> {
+        Some(line.trim().to_string())
+    }
+}
+
+#[test]
+fn timers_fire_on_the_virtual_clock() {
+    let mut sim = Simulation::<Clock>::with_players(1, 1).unwrap();
+    sim.send_line("player1", "turn").unwrap();
+
+    sim.advance(Duration::from_millis(4_900));
+    assert!(sim.state().fired.is_empty());
+
+    sim.advance(Duration::from_millis(100));
+    assert_eq!(sim.state().fired, vec![("turn".to_string(), 5_000)]);
+    sim.assert_message("player1", "turn is up");
+}
+
+#[test]
+fn rescheduling_moves_the_deadline_and_cancel_forgets_it() {
+    let mut sim = Simulation::<Clock>::with_players(1, 1).unwrap();
+    sim.send_line("player1", "turn").unwrap();
+    sim.advance(Duration::from_secs(3));
+    sim.send_line("player1", "turn").unwrap();
+    sim.advance(Duration::from_secs(3));
+    assert!(sim.state().fired.is_empty());
+
+    sim.send_line("player1", "cancel").unwrap();
+    sim.advance(Duration::from_secs(10));
+    assert!(sim.state().fired.is_empty());
+}
+
+#[test]
+fn ticks_follow_the_tick_rate() {
+    let mut sim = Simulation::<Clock>::new(1);
+    sim.advance(Duration::from_secs(2));
+    assert_eq!(sim.state().ticks, 20);
+    assert_eq!(sim.now(), Duration::from_secs(2));
+}